@@ -0,0 +1,73 @@
+use crate::pipeline::{frame_index, frame_position};
+use std::time::Duration;
+
+/// Finds the sounding segments of an audio track, separated by silences, e.g. phrases of a
+/// recording with long pauses between them.
+///
+/// A frame is silent, if all its samples are quieter than the threshold. Silences, shorter than
+/// the minimal duration, are kept inside the segments, so short pauses, e.g. between words, don't
+/// split them. Silence at the beginning and the end of the audio track is left out at any length.
+///
+/// Returns the beginnings and the ends of the segments, in the order of playback. These are the
+/// suggested cut points.
+///
+/// # Parameters
+///
+/// * `samples` - interleaved samples of all channels of the audio track.
+/// * `channels` - number of channels of the audio track.
+/// * `sample_rate` - number of frames per second of the audio track.
+/// * `threshold` - amplitude, below which samples are silent, where `1.0` is the full scale.
+/// * `min_silence` - minimal duration of a silence, that separates the segments.
+pub fn find_sound_segments(
+    samples: impl Iterator<Item = f32>,
+    channels: u16,
+    sample_rate: u32,
+    threshold: f32,
+    min_silence: Duration,
+) -> Vec<(Duration, Duration)> {
+    if channels == 0 {
+        return Vec::new();
+    }
+    let min_silence_frames = frame_index(min_silence, sample_rate).max(1);
+
+    let mut segments = Vec::new();
+    let mut segment_start = None;
+    let mut silence_start = None;
+    let mut frame = 0;
+    let mut frame_peak = 0.0_f32;
+    for (index, sample) in samples.enumerate() {
+        frame_peak = frame_peak.max(sample.abs());
+        if (index + 1) % usize::from(channels) != 0 {
+            continue;
+        }
+
+        if frame_peak < threshold {
+            silence_start.get_or_insert(frame);
+        } else {
+            if let Some(start) = silence_start.take() {
+                if frame - start >= min_silence_frames {
+                    if let Some(segment_start) = segment_start.take() {
+                        segments.push((segment_start, start));
+                    }
+                }
+            }
+            segment_start.get_or_insert(frame);
+        }
+        frame += 1;
+        frame_peak = 0.0;
+    }
+
+    // Silence at the end of the audio track doesn't separate anything, so it is left out
+    if let Some(start) = segment_start {
+        segments.push((start, silence_start.unwrap_or(frame)));
+    }
+    segments
+        .into_iter()
+        .map(|(start, end)| {
+            (
+                frame_position(start, sample_rate),
+                frame_position(end, sample_rate),
+            )
+        })
+        .collect()
+}
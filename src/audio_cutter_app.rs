@@ -1,234 +1,2748 @@
-use crate::audio_thread::{self, AudioControlCommand};
-use eframe::egui::{self, containers::Frame, emath, epaint, epaint::PathStroke};
-use rodio::Source;
-use std::fs::File;
-
-/// Current audio playback status.
-enum PlaybackStatus {
-    Playing,
-    Stopped,
+use crate::audio_thread::{self, AudioControlCommand, PlaybackStatus};
+use crate::export::{ChannelMode, ExportFormat, ExportProgress, Mp3Bitrate};
+use crate::i18n::{self, tr, tr_args, Key, Lang};
+use crate::pipeline::{self, AudioPipeline};
+use crate::spectrogram::Spectrogram;
+use crate::waveform::Waveform;
+use eframe::egui::{self, containers::Frame, emath, epaint};
+
+/// Storage key for the preference of showing the full path of the opened file.
+const SHOW_FULL_PATH_KEY: &str = "show_full_path";
+/// Storage key for the directory, from which an audio file was opened last time.
+const LAST_OPEN_DIRECTORY_KEY: &str = "last_open_directory";
+/// Storage key for the code of the user interface language.
+const LANGUAGE_KEY: &str = "language";
+/// Storage key for what playback does at the end of the track or of the chosen region.
+const LOOP_MODE_KEY: &str = "loop_mode";
+/// Storage key for the preference of showing the spectrogram instead of the sound wave.
+const SHOW_SPECTROGRAM_KEY: &str = "show_spectrogram";
+/// Storage key for the way, decoded audio tracks are kept.
+const LOAD_MODE_KEY: &str = "load_mode";
+/// Storage key for the scale of the user interface.
+const UI_SCALE_KEY: &str = "ui_scale";
+/// Storage key for the positions and the regions of the audio files, worked on recently.
+const FILE_STATES_KEY: &str = "file_states";
+/// Storage key for the audio files, opened recently.
+const RECENT_FILES_KEY: &str = "recent_files";
+/// Maximum number of recently opened audio files to remember.
+const RECENT_FILES_LIMIT: usize = 5;
+/// Maximum number of audio files to remember the position and the regions of.
+const FILE_STATES_LIMIT: usize = 50;
+/// Maximum number of region changes, that can be undone.
+const UNDO_LIMIT: usize = 50;
+/// Default duration of fade-in and fade-out of the exported region.
+const DEFAULT_FADE_DURATION: std::time::Duration = std::time::Duration::from_millis(50);
+/// Initial duration of the crossfade between the joined regions.
+const DEFAULT_CROSSFADE_DURATION: std::time::Duration = std::time::Duration::from_millis(500);
+/// Duration of the crossfade, that joins the parts of the audio track around the cut out region,
+/// so there is no click at the join.
+const CUT_OUT_CROSSFADE_DURATION: std::time::Duration = std::time::Duration::from_millis(20);
+/// Initial duration of the silence between the joined regions.
+const DEFAULT_GAP_DURATION: std::time::Duration = std::time::Duration::from_millis(1000);
+/// Initial level in dBFS, below which the audio track is silent, when it is split at silences.
+const DEFAULT_SILENCE_THRESHOLD_DB: f32 = -40.0;
+/// Initial minimal duration of a silence, the audio track is split at.
+const DEFAULT_MIN_SILENCE_DURATION: std::time::Duration = std::time::Duration::from_millis(500);
+/// Shortest duration of the audio track, shown on the sound wave, when it is zoomed in to the
+/// maximum.
+const MIN_VISIBLE_WAVE_DURATION: f32 = 0.2;
+/// Factor, by which the sound wave is zoomed in or out with buttons.
+const WAVE_ZOOM_STEP: f32 = 2.0;
+/// Minimal interval between seeks, while user drags the position slider.
+const SCRUB_SEEK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+/// Duration before the end of the audio track, to which the position jumps to the end, so the
+/// very end is still heard and the track ends on its own.
+const END_JUMP_LEAD: std::time::Duration = std::time::Duration::from_secs(1);
+/// Step, by which the current position is moved with arrow keys.
+const SEEK_STEP: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// What playback does, once it reaches the end of the audio track or of the chosen region.
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+enum LoopMode {
+    /// Play the rest of the audio track once and stop at its end.
+    #[default]
+    Off,
+    /// Restart the whole audio track from the beginning, when it is played to its end.
+    RepeatTrack,
+    /// Play the chosen region over and over, instead of the rest of the audio track, e.g. for
+    /// practicing passages one after another.
+    LoopRegion,
+    /// Play the chosen region only, stopping at its end, instead of the rest of the audio track.
+    PlayRegionOnly,
+}
+
+/// Regions, chosen by user, as a whole, for undoing and redoing their changes.
+#[derive(Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+struct RegionState {
+    start: Option<std::time::Duration>,
+    end: Option<std::time::Duration>,
+    regions: Vec<(std::time::Duration, std::time::Duration)>,
+}
+
+/// Position and regions of an audio file, remembered to continue the work on it, when it is
+/// opened again.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct FileState {
+    /// Path to the audio file.
+    file: std::path::PathBuf,
+    /// Last modification time of the audio file, when it was loaded. The state is not restored,
+    /// if the audio file is changed since then, as positions make no sense for other audio.
+    modified: std::time::SystemTime,
+    /// Position on the audio wave.
+    position: f32,
+    /// Regions, chosen by user.
+    region_state: RegionState,
+}
+
+/// Audio track, opened in a tab, that is not the current one, along with the state of its editing.
+///
+/// The current audio track is kept in [AudioCutterApp] itself, and is swapped with a [Tab] on
+/// switching tabs.
+struct Tab {
+    /// Path to the audio file of the audio track.
+    file: std::path::PathBuf,
+    /// Audio pipeline, that owns the decoded audio track.
+    pipeline: AudioPipeline,
+    /// Sound wave of the audio track.
+    waveform: Waveform,
+    /// Spectrogram of the audio track, if it is computed.
+    spectrogram: Option<Spectrogram>,
+    /// Receiver of the spectrogram of the audio track, while it is computed.
+    spectrogram_receiver: Option<std::sync::mpsc::Receiver<Spectrogram>>,
+    /// Position on the audio wave, where playback is continued from.
+    position: f32,
+    /// Zoom of the sound wave.
+    wave_zoom: f32,
+    /// Position in seconds, from which the zoomed sound wave is shown.
+    wave_view_start: f32,
+    /// Regions, chosen by user.
+    region_state: RegionState,
+    /// Undo history of the regions.
+    undo_stack: Vec<RegionState>,
+    /// Redo history of the regions.
+    redo_stack: Vec<RegionState>,
+    /// Region state, recorded last time to the undo history.
+    recorded_region_state: RegionState,
+    /// Non-zero position on the audio wave, at which playback was paused or stopped last time.
+    last_pause_position: Option<f32>,
+    /// Outcome of the last region export.
+    export_result: Option<Result<String, String>>,
+    /// Progress and receiver of the outcome of the region export, while it runs.
+    export_task: Option<ExportTask>,
+}
+
+/// Progress of a region export, running in the background, and receiver of its outcome.
+type ExportTask = (
+    ExportProgress,
+    std::sync::mpsc::Receiver<Result<String, String>>,
+);
+
+/// Synchronous operation over the application, that blocks the UI thread for a while.
+type BusyTask = Box<dyn FnOnce(&mut AudioCutterApp)>;
+
+/// AudioCutterApp controls application UI.
+pub struct AudioCutterApp {
+    playback_status: PlaybackStatus,
+    /// Number of audio sources, sent to the audio thread for playing, that it hasn't reported
+    /// as playing yet. Until then, statuses reported by the audio thread are about audio sources,
+    /// replaced by them, so they are outdated.
+    pending_plays: usize,
+
+    /// AudioThread controls separate thread that performs audio playback process.
+    audio_thread: Option<audio_thread::AudioThread>,
+
+    /// Current audio track filename, chosen by user.
+    current_file_name: Option<std::path::PathBuf>,
+    /// Tabs of all opened audio tracks in the order of opening. The slot of the current one is
+    /// empty, as the current audio track is kept in the application itself.
+    tabs: Vec<Option<Tab>>,
+    /// Index of the tab of the current audio track.
+    current_tab: usize,
+    /// Directory, from which an audio file was opened last time. Open file dialog starts there.
+    last_open_directory: Option<std::path::PathBuf>,
+    /// Audio files, loaded recently, starting from the latest one.
+    recent_files: Vec<std::path::PathBuf>,
+    /// Positions and regions of the audio files, worked on recently, starting from the latest
+    /// one.
+    file_states: Vec<FileState>,
+    /// Audio pipeline, that owns the decoded current audio track.
+    pipeline: Option<AudioPipeline>,
+    /// Error message of the last failed attempt to load an audio track.
+    load_error: Option<String>,
+    /// Error message of the last failed attempt to show the opened file in the file manager.
+    reveal_error: Option<String>,
+    /// Sound wave of the current audio track, computed once it is loaded.
+    waveform: Waveform,
+    /// Spectrogram of the current audio track, computed in the background, once it is shown.
+    spectrogram: Option<Spectrogram>,
+    /// Receiver of the spectrogram of the current audio track, while it is computed.
+    spectrogram_receiver: Option<std::sync::mpsc::Receiver<Spectrogram>>,
+    /// Image of the visible part of the spectrogram, uploaded for drawing, along with the range of
+    /// the columns and the width, it was made for.
+    spectrogram_texture: Option<(std::ops::Range<usize>, usize, egui::TextureHandle)>,
+    /// Duration of the part of the audio source, that user skips before sending an audio source
+    /// to the AudioThread.
+    skipped_from_beg: std::time::Duration,
+    /// Position of the currently chosen or playing sample on the audio wave.
+    audio_wave_position: f32,
+    /// Whether user is dragging the position slider, so the position is not taken from playback.
+    scrubbing: bool,
+    /// Zoom of the sound wave, where `1.0` shows the whole audio track.
+    wave_zoom: f32,
+    /// Position in seconds, from which the zoomed sound wave is shown.
+    wave_view_start: f32,
+    /// Moment and position of the last seek, made while dragging the position slider.
+    last_scrub_seek: Option<(std::time::Instant, f32)>,
+    /// Region bound, that stays in place, while user drags across the sound wave to choose the
+    /// region. The other bound follows the pointer.
+    region_drag_anchor: Option<std::time::Duration>,
+    /// Beginning of the region of the current audio track, chosen by user for cutting.
+    region_start: Option<std::time::Duration>,
+    /// End of the region of the current audio track, chosen by user for cutting.
+    region_end: Option<std::time::Duration>,
+    /// Text of the region beginning entry, as typed by user.
+    region_start_text: String,
+    /// Text of the region end entry, as typed by user.
+    region_end_text: String,
+    /// Whether to fade the exported region in from silence.
+    fade_in: bool,
+    /// Duration of the fade-in of the exported region.
+    fade_in_duration: std::time::Duration,
+    /// Whether to fade the exported region out to silence.
+    fade_out: bool,
+    /// Duration of the fade-out of the exported region.
+    fade_out_duration: std::time::Duration,
+    /// Whether to scale the exported regions, so their peaks hit the same level. Unlike the
+    /// volume, that affects playback only.
+    normalize: bool,
+    /// Gain in decibels to amplify or attenuate the exported regions by.
+    gain_db: f32,
+    /// Whether to hard-limit the amplified samples of the exported regions.
+    limit: bool,
+    /// Format of the exported regions.
+    export_format: ExportFormat,
+    /// Channels of the exported regions.
+    channel_mode: ChannelMode,
+    /// Whether to overlap the adjacent regions, when joining them into a single file.
+    crossfade: bool,
+    /// Duration of the crossfade between the joined regions.
+    crossfade_duration: std::time::Duration,
+    /// Whether to separate the adjacent regions with silence, when joining them into a single
+    /// file. It excludes the crossfade.
+    gap: bool,
+    /// Duration of the silence between the joined regions.
+    gap_duration: std::time::Duration,
+    /// Level in dBFS, below which the audio track is silent, when it is split at silences.
+    silence_threshold_db: f32,
+    /// Minimal duration of a silence, the audio track is split at.
+    min_silence_duration: std::time::Duration,
+    /// Regions, chosen by user for cutting into separate files, sorted by their beginnings and
+    /// never overlapping.
+    regions: Vec<(std::time::Duration, std::time::Duration)>,
+    /// Region state before each change, that can be undone, starting from the oldest one.
+    undo_stack: Vec<RegionState>,
+    /// Region state before each undo, that can be redone, starting from the oldest one.
+    redo_stack: Vec<RegionState>,
+    /// Region state, recorded last time to the undo history.
+    recorded_region_state: RegionState,
+    /// Outcome of the last region export: message about the written files or error message.
+    export_result: Option<Result<String, String>>,
+    /// Progress and receiver of the outcome of the region export, while it runs in the
+    /// background.
+    export_task: Option<ExportTask>,
+    /// Whether the current position has been moved back by stopping playback, rather than chosen
+    /// by user or left by a pause. Playback then starts from the beginning of the region, if one
+    /// is chosen.
+    rewound: bool,
+    /// Non-zero position on the audio wave, at which playback was paused or stopped last time.
+    last_pause_position: Option<f32>,
+    /// Playback volume, where `1.0` is the original one. Kept while muted, so it is restored on
+    /// unmute.
+    volume: f32,
+    /// Whether playback is muted regardless of the volume.
+    muted: bool,
+    /// Playback speed factor, where `1.0` is the original speed.
+    speed: f32,
+    /// What playback does at the end of the track or of the chosen region. The modes exclude
+    /// each other, so it is never ambiguous, e.g. whether the track or the region is repeated. It
+    /// is kept between sessions and tracks.
+    loop_mode: LoopMode,
+    /// Whether to play the chosen region processed, as it is exported, instead of the original
+    /// audio, so user can compare them.
+    preview_processed: bool,
+    /// Whether to show the full path of the opened file instead of its name only.
+    show_full_path: bool,
+    /// Whether to show the spectrogram of the audio track instead of its sound wave.
+    show_spectrogram: bool,
+    /// Whether to show the bottom status bar with audio sink diagnostics.
+    show_sink_status: bool,
+    /// Way, audio tracks are kept after they are decoded, applied to audio files, loaded later.
+    load_mode: pipeline::LoadMode,
+    /// Scale of the whole user interface, where `1.0` is the native one. Kept in sync with the
+    /// egui zoom factor, that can also be changed with Ctrl+Plus and Ctrl+Minus.
+    ui_scale: f32,
+    /// Interval between elapsed time updates, used by the audio thread while the window is
+    /// shown.
+    update_interval: std::time::Duration,
+    /// Interval between elapsed time updates, used by the audio thread while the window is
+    /// hidden.
+    hidden_update_interval: std::time::Duration,
+    /// Blocking operation, scheduled to run once the busy indication is shown.
+    busy_task: Option<BusyTask>,
+    /// Whether the busy indication for `busy_task` has already been shown.
+    busy_task_shown: bool,
 }
 
-/// AudioCutterApp controls application UI.
-pub struct AudioCutterApp {
-    playback_status: PlaybackStatus,
+impl AudioCutterApp {
+    /// Creates a new [AudioCutterApp], restoring user preferences saved by the previous run.
+    ///
+    /// # Parameters
+    ///
+    /// * `cc` - eframe creation context, that gives access to the persistent storage.
+    /// * `file` - path to the audio file to open on startup, e.g. given as a command-line
+    ///   argument.
+    pub fn new(cc: &eframe::CreationContext<'_>, file: Option<std::path::PathBuf>) -> Self {
+        let mut app = Self::default();
+        // The language is not stored on the first run, so the system one is used then
+        let lang = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<String>(storage, LANGUAGE_KEY))
+            .and_then(|code| Lang::from_code(&code))
+            .unwrap_or_else(Lang::system);
+        i18n::set_lang(lang);
+        if let Some(storage) = cc.storage {
+            app.show_full_path = eframe::get_value(storage, SHOW_FULL_PATH_KEY).unwrap_or_default();
+            app.last_open_directory =
+                eframe::get_value(storage, LAST_OPEN_DIRECTORY_KEY).unwrap_or_default();
+            app.recent_files = eframe::get_value(storage, RECENT_FILES_KEY).unwrap_or_default();
+            app.file_states = eframe::get_value(storage, FILE_STATES_KEY).unwrap_or_default();
+            app.loop_mode = eframe::get_value(storage, LOOP_MODE_KEY).unwrap_or_default();
+            app.show_spectrogram =
+                eframe::get_value(storage, SHOW_SPECTROGRAM_KEY).unwrap_or_default();
+            app.load_mode = eframe::get_value(storage, LOAD_MODE_KEY).unwrap_or_default();
+            app.ui_scale = eframe::get_value(storage, UI_SCALE_KEY).unwrap_or(1.0);
+        }
+        cc.egui_ctx.set_zoom_factor(app.ui_scale);
+        if let Some(file) = file {
+            app.open_supported_file(file);
+        }
+        app
+    }
+
+    /// Controls the behavior of opening file UI button.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the button on.
+    fn open_file_button(&mut self, ui: &mut egui::Ui) {
+        if ui.button(tr(Key::OpenFile)).clicked() {
+            // The first filter is the default one, so user sees all audio files at once
+            let mut dialog = rfd::FileDialog::new()
+                .add_filter(tr(Key::AudioFilesFilter), crate::SUPPORTED_EXTENSIONS)
+                .add_filter(tr_args(Key::FileFilter, &[&"MP3"]), &["mp3"])
+                .add_filter(tr_args(Key::FileFilter, &[&"WAV"]), &["wav"])
+                .add_filter(tr_args(Key::FileFilter, &[&"FLAC"]), &["flac"])
+                .add_filter(tr_args(Key::FileFilter, &[&"OGG Vorbis"]), &["ogg"]);
+            // The directory could be removed or renamed since it was stored
+            if let Some(directory) = self
+                .last_open_directory
+                .as_ref()
+                .filter(|directory| directory.is_dir())
+            {
+                dialog = dialog.set_directory(directory);
+            }
+
+            if let Some(file) = dialog.pick_file() {
+                self.open_file(file);
+            }
+        }
+    }
+
+    /// Schedules loading of the audio track from the given file.
+    ///
+    /// # Parameters
+    ///
+    /// * `file` - path to the audio file, chosen by user.
+    fn open_file(&mut self, file: std::path::PathBuf) {
+        self.last_open_directory = file.parent().map(std::path::Path::to_path_buf);
+
+        // Decoding may take a while, so let user know the app is not frozen
+        self.schedule_busy_task(move |app| app.load_file(file));
+    }
+
+    /// Opens the audio file, dropped onto the window by user, and shows a hint while a file is
+    /// dragged over the window.
+    ///
+    /// # Parameters
+    ///
+    /// * `ctx` - UI context for reading dropped files from and showing the hint on.
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let painter = ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground,
+                egui::Id::new("drop_overlay"),
+            ));
+            let screen_rect = ctx.screen_rect();
+            painter.rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(128));
+            painter.text(
+                screen_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                tr(Key::DropHint),
+                egui::FontId::proportional(24.0),
+                egui::Color32::WHITE,
+            );
+        }
+
+        // Only one audio track can be opened at once, so the first dropped file is taken
+        let Some(file) = ctx.input(|i| i.raw.dropped_files.iter().find_map(|f| f.path.clone()))
+        else {
+            return;
+        };
+
+        self.open_supported_file(file);
+    }
+
+    /// Opens the audio file, given not by the open file dialog, if its extension is one of
+    /// [crate::SUPPORTED_EXTENSIONS], otherwise stores the error for showing to user.
+    ///
+    /// # Parameters
+    ///
+    /// * `file` - path to the audio file, chosen by user.
+    fn open_supported_file(&mut self, file: std::path::PathBuf) {
+        let supported = file
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                crate::SUPPORTED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+            });
+        if supported {
+            self.open_file(file);
+        } else {
+            self.load_error = Some(tr_args(
+                Key::UnsupportedFile,
+                &[
+                    &file.to_string_lossy(),
+                    &crate::SUPPORTED_EXTENSIONS.join(", "),
+                ],
+            ));
+        }
+    }
+
+    /// Loads the audio track from the given file and makes it the current one.
+    ///
+    /// If the track can't be used, the error is stored for showing to user and the current track,
+    /// if any, stays as it is.
+    ///
+    /// # Parameters
+    ///
+    /// * `file` - path to the audio file to load.
+    fn load_file(&mut self, file: std::path::PathBuf) {
+        // The file is already opened in another tab, so just switch to it
+        if let Some(index) = self
+            .tabs
+            .iter()
+            .position(|tab| tab.as_ref().is_some_and(|tab| tab.file == file))
+        {
+            self.switch_tab(index);
+            return;
+        }
+
+        match AudioPipeline::load_with_mode(&file, self.load_mode) {
+            Ok(pipeline) => {
+                // Stop playing current audio if a new file is loaded, keeping its position in its
+                // tab
+                self.pause_for_tab_change();
+                self.playback_status = PlaybackStatus::Stopped;
+                // The file could be reloaded in place, so its state is remembered before it is
+                // taken back
+                self.remember_current_file_state();
+                let file_state = self.take_file_state(&file, pipeline.modified());
+                // The file, opened in the current tab, is reloaded in place, and others get a new
+                // tab
+                if self.current_file_name.as_ref() != Some(&file) {
+                    if self.current_file_name.is_some() {
+                        self.tabs[self.current_tab] = Some(self.take_tab());
+                    }
+                    self.tabs.push(None);
+                    self.current_tab = self.tabs.len() - 1;
+                }
+                self.audio_wave_position = 0.0;
+                self.skipped_from_beg = std::time::Duration::ZERO;
+                self.waveform = pipeline.compute_waveform();
+                self.spectrogram = None;
+                self.spectrogram_receiver = None;
+                self.spectrogram_texture = None;
+                self.wave_zoom = 1.0;
+                self.wave_view_start = 0.0;
+                self.pipeline = Some(pipeline);
+                self.remember_recent_file(&file);
+                self.current_file_name = Some(file);
+                self.rewound = true;
+                self.last_pause_position = None;
+                self.region_start = None;
+                self.region_end = None;
+                self.regions.clear();
+                // Changes of the regions of the previous track make no sense anymore
+                self.undo_stack.clear();
+                self.redo_stack.clear();
+                self.recorded_region_state = RegionState::default();
+                self.export_result = None;
+                self.export_task = None;
+                self.load_error = None;
+                self.reveal_error = None;
+
+                // Continue the work on the file, where it was left last time
+                if let Some(file_state) = file_state {
+                    let duration = self.pipeline.as_ref().unwrap().duration().as_secs_f32();
+                    self.audio_wave_position = file_state.position.min(duration);
+                    self.rewound = false;
+                    self.set_region_state(file_state.region_state.clone());
+                    self.recorded_region_state = file_state.region_state;
+                }
+            }
+            // E.g. the file has a supported extension, but it is not an audio file actually
+            Err(error) => {
+                let file_name = file.file_name().unwrap_or(file.as_os_str());
+                self.load_error = Some(tr_args(
+                    Key::LoadFailed,
+                    &[&file_name.to_string_lossy(), &error],
+                ));
+            }
+        }
+    }
+
+    /// Takes the current audio track out of the application into a tab, leaving no current audio
+    /// track.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no current audio track.
+    fn take_tab(&mut self) -> Tab {
+        Tab {
+            file: self.current_file_name.take().unwrap(),
+            pipeline: self.pipeline.take().unwrap(),
+            waveform: std::mem::take(&mut self.waveform),
+            spectrogram: self.spectrogram.take(),
+            spectrogram_receiver: self.spectrogram_receiver.take(),
+            position: std::mem::take(&mut self.audio_wave_position),
+            wave_zoom: std::mem::replace(&mut self.wave_zoom, 1.0),
+            wave_view_start: std::mem::take(&mut self.wave_view_start),
+            region_state: RegionState {
+                start: self.region_start.take(),
+                end: self.region_end.take(),
+                regions: std::mem::take(&mut self.regions),
+            },
+            undo_stack: std::mem::take(&mut self.undo_stack),
+            redo_stack: std::mem::take(&mut self.redo_stack),
+            recorded_region_state: std::mem::take(&mut self.recorded_region_state),
+            last_pause_position: self.last_pause_position.take(),
+            export_result: self.export_result.take(),
+            export_task: self.export_task.take(),
+        }
+    }
+
+    /// Makes the audio track of the tab the current one.
+    ///
+    /// # Parameters
+    ///
+    /// * `tab` - the tab to restore.
+    fn restore_tab(&mut self, tab: Tab) {
+        self.current_file_name = Some(tab.file);
+        self.pipeline = Some(tab.pipeline);
+        self.waveform = tab.waveform;
+        self.spectrogram = tab.spectrogram;
+        self.spectrogram_receiver = tab.spectrogram_receiver;
+        self.spectrogram_texture = None;
+        self.audio_wave_position = tab.position;
+        self.skipped_from_beg = std::time::Duration::ZERO;
+        self.wave_zoom = tab.wave_zoom;
+        self.wave_view_start = tab.wave_view_start;
+        self.set_region_state(tab.region_state);
+        self.undo_stack = tab.undo_stack;
+        self.redo_stack = tab.redo_stack;
+        self.recorded_region_state = tab.recorded_region_state;
+        self.last_pause_position = tab.last_pause_position;
+        self.export_result = tab.export_result;
+        self.export_task = tab.export_task;
+        self.reveal_error = None;
+        // Continue from the position, left in the tab, not from the region beginning
+        self.rewound = false;
+    }
+
+    /// Pauses playback of the current audio track, if playing, keeping its position.
+    fn pause_for_tab_change(&mut self) {
+        if let PlaybackStatus::Playing = self.playback_status {
+            self.audio_wave_position = self.current_position().as_secs_f32();
+            self.send_command(AudioControlCommand::Stop);
+            self.playback_status = PlaybackStatus::Stopped;
+        }
+    }
+
+    /// Makes the audio track of the given tab the current one, stopping playback of the current
+    /// one.
+    ///
+    /// # Parameters
+    ///
+    /// * `index` - index of the tab to switch to.
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.current_tab {
+            return;
+        }
+
+        self.pause_for_tab_change();
+        self.tabs[self.current_tab] = Some(self.take_tab());
+        let tab = self.tabs[index].take().unwrap();
+        self.restore_tab(tab);
+        self.current_tab = index;
+    }
+
+    /// Closes the tab of the current audio track, switching to the next tab, or to the previous
+    /// one, if it is the last tab.
+    fn close_current_tab(&mut self) {
+        self.pause_for_tab_change();
+        self.remember_current_file_state();
+        drop(self.take_tab());
+        self.tabs.remove(self.current_tab);
+        self.current_tab = self.current_tab.min(self.tabs.len().saturating_sub(1));
+        if let Some(tab) = self.tabs.get_mut(self.current_tab).and_then(Option::take) {
+            self.restore_tab(tab);
+        }
+    }
+
+    /// Controls the tabs of the opened audio tracks, that switch the current one and close them.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the tabs on.
+    fn tab_bar(&mut self, ui: &mut egui::Ui) {
+        let mut chosen_tab = None;
+        let mut close_current = false;
+        ui.horizontal_wrapped(|ui| {
+            for (index, tab) in self.tabs.iter().enumerate() {
+                let file = match tab {
+                    Some(tab) => &tab.file,
+                    None => self.current_file_name.as_ref().unwrap(),
+                };
+                let name = file
+                    .file_name()
+                    .unwrap_or(file.as_os_str())
+                    .to_string_lossy();
+                let current = index == self.current_tab;
+                if ui
+                    .selectable_label(current, elide_middle(&name, 24))
+                    .on_hover_text(file.to_string_lossy())
+                    .clicked()
+                {
+                    chosen_tab = Some(index);
+                }
+                if current
+                    && ui
+                        .small_button("✖")
+                        .on_hover_text(tr(Key::CloseTab))
+                        .clicked()
+                {
+                    close_current = true;
+                }
+            }
+        });
+
+        if close_current {
+            self.close_current_tab();
+        } else if let Some(index) = chosen_tab {
+            self.switch_tab(index);
+        }
+    }
+
+    /// Puts the file at the top of the recently opened files, keeping at most
+    /// [RECENT_FILES_LIMIT] of them.
+    ///
+    /// # Parameters
+    ///
+    /// * `file` - path to the audio file, that has been loaded.
+    fn remember_recent_file(&mut self, file: &std::path::Path) {
+        self.recent_files.retain(|recent_file| recent_file != file);
+        self.recent_files.insert(0, file.to_path_buf());
+        self.recent_files.truncate(RECENT_FILES_LIMIT);
+    }
+
+    /// Remembers the position and the regions of the current audio track, if there is one. See
+    /// [Self::remember_file_state].
+    fn remember_current_file_state(&mut self) {
+        let (Some(file), Some(pipeline)) = (&self.current_file_name, &self.pipeline) else {
+            return;
+        };
+        // The file can't be checked for changes without its modification time
+        let Some(modified) = pipeline.modified() else {
+            return;
+        };
+        let state = FileState {
+            file: file.clone(),
+            modified,
+            position: self.current_position().as_secs_f32(),
+            region_state: self.region_state(),
+        };
+        self.remember_file_state(state);
+    }
+
+    /// Puts the position and the regions of an audio file at the top of the remembered ones,
+    /// keeping at most [FILE_STATES_LIMIT] of them.
+    ///
+    /// # Parameters
+    ///
+    /// * `state` - the position and the regions of the audio file.
+    fn remember_file_state(&mut self, state: FileState) {
+        self.file_states
+            .retain(|file_state| file_state.file != state.file);
+        self.file_states.insert(0, state);
+        self.file_states.truncate(FILE_STATES_LIMIT);
+    }
+
+    /// Takes the remembered position and regions of the audio file out, if it is not changed
+    /// since they were remembered.
+    ///
+    /// # Parameters
+    ///
+    /// * `file` - path to the audio file.
+    /// * `modified` - current modification time of the audio file, if it is known.
+    fn take_file_state(
+        &mut self,
+        file: &std::path::Path,
+        modified: Option<std::time::SystemTime>,
+    ) -> Option<FileState> {
+        let index = self
+            .file_states
+            .iter()
+            .position(|file_state| file_state.file == file)?;
+        // Changed file gets a fresh start, so its outdated state is forgotten
+        let state = self.file_states.remove(index);
+        (Some(state.modified) == modified).then_some(state)
+    }
+
+    /// Controls the menu with recently opened files, that opens the chosen one.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the menu on.
+    fn recent_files_menu(&mut self, ui: &mut egui::Ui) {
+        // The files could be removed or renamed since they were opened
+        let recent_files: Vec<std::path::PathBuf> = self
+            .recent_files
+            .iter()
+            .filter(|file| file.is_file())
+            .cloned()
+            .collect();
+        if recent_files.is_empty() {
+            return;
+        }
+
+        let mut chosen_file = None;
+        ui.menu_button(tr(Key::RecentFiles), |ui| {
+            for file in recent_files {
+                let name = file.file_name().unwrap_or_default().to_string_lossy();
+                if ui
+                    .button(name)
+                    .on_hover_text(file.to_string_lossy())
+                    .clicked()
+                {
+                    chosen_file = Some(file.clone());
+                    ui.close();
+                }
+            }
+        });
+
+        if let Some(file) = chosen_file {
+            self.open_file(file);
+        }
+    }
+
+    /// Schedules a synchronous operation, that blocks the UI thread for a while, to run deferred,
+    /// showing busy cursor and overlay until it completes.
+    ///
+    /// The operation does not run right away, but on one of the next frames, so callers must not
+    /// expect its outcome after this returns. egui shows a frame only after
+    /// [eframe::App::update] returns, so running the operation right away would freeze the
+    /// previous frame without any indication. Instead, the next frame only shows the busy
+    /// indication, and the operation runs on the frame after it. See [Self::run_busy_task].
+    ///
+    /// There is no scoped guard around the operation, as the busy indication is drawn on each
+    /// frame from the scheduled operation itself. The operation is taken out before it runs, so
+    /// the indication is gone whatever its outcome is, including a panic.
+    ///
+    /// # Parameters
+    ///
+    /// * `task` - the operation to run.
+    fn schedule_busy_task(&mut self, task: impl FnOnce(&mut Self) + 'static) {
+        self.busy_task = Some(Box::new(task));
+        self.busy_task_shown = false;
+    }
+
+    /// Shows busy indication for the task, scheduled by [Self::schedule_busy_task], or runs the
+    /// task if the indication has already been shown.
+    ///
+    /// # Parameters
+    ///
+    /// * `ctx` - UI context for showing the busy indication on.
+    fn run_busy_task(&mut self, ctx: &egui::Context) {
+        if self.busy_task.is_none() {
+            return;
+        }
+
+        if self.busy_task_shown {
+            // The task is taken out before running, so busy indication is gone on the next
+            // frame whatever the task outcome is
+            self.busy_task_shown = false;
+            let task = self.busy_task.take().unwrap();
+            task(self);
+            return;
+        }
+
+        ctx.set_cursor_icon(egui::CursorIcon::Progress);
+        ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("busy_overlay"),
+        ))
+        .rect_filled(ctx.screen_rect(), 0.0, egui::Color32::from_black_alpha(64));
+        self.busy_task_shown = true;
+        ctx.request_repaint();
+    }
+
+    /// Controls the line with information about the opened file.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the file information on.
+    fn file_info(&mut self, ui: &mut egui::Ui) {
+        let picked_file = self.current_file_name.as_ref().unwrap();
+        // Paths are not required to be valid UTF-8, so don't fail on showing them
+        let full_path = picked_file.to_string_lossy().into_owned();
+
+        ui.horizontal(|ui| {
+            ui.label(tr(Key::OpenedFile));
+            if self.show_full_path {
+                ui.label(elide_middle(&full_path, 60))
+                    .on_hover_text(&full_path);
+            } else {
+                ui.label(picked_file.file_name().unwrap().to_string_lossy());
+            }
+            if let Some(badge) = channels_badge(self.pipeline.as_ref().unwrap().channels()) {
+                ui.label(egui::RichText::new(badge).strong());
+            }
+            if ui
+                .button("📋")
+                .on_hover_text(tr(Key::CopyFullPath))
+                .clicked()
+            {
+                ui.ctx().copy_text(full_path);
+            }
+            if ui
+                .button("📂")
+                .on_hover_text(tr(Key::RevealInFileManager))
+                .clicked()
+            {
+                self.reveal_error = reveal_in_file_manager(picked_file).err();
+            }
+            ui.checkbox(&mut self.show_full_path, tr(Key::FullPath));
+        });
+
+        if let Some(error) = &self.reveal_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    /// Controls the collapsible section with audio properties of the opened file.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the section on.
+    fn file_details(&self, ui: &mut egui::Ui) {
+        let pipeline = self.pipeline.as_ref().unwrap();
+        egui::CollapsingHeader::new(tr(Key::FileDetails)).show(ui, |ui| {
+            ui.label(tr_args(Key::SampleRate, &[&pipeline.sample_rate()]));
+            ui.label(tr_args(Key::Channels, &[&pipeline.channels()]));
+            if let Some(bitrate) = pipeline.bitrate() {
+                ui.label(tr_args(Key::AverageBitrate, &[&bitrate]));
+            }
+            if pipeline.streamed() {
+                ui.label(tr(Key::StreamedFromDisk));
+            }
+        });
+    }
+
+    /// Starts playback of the current audio track from the given position.
+    ///
+    /// # Parameters
+    ///
+    /// * `position` - duration of the part of the audio track to skip before playing.
+    fn play_from(&mut self, position: std::time::Duration) {
+        // Sink keeps its volume and speed between audio sources, but apply them anyway, so
+        // playback never starts with values other than the chosen ones
+        self.send_command(AudioControlCommand::SetVolume(self.volume));
+        self.send_command(AudioControlCommand::SetMuted(self.muted));
+        self.send_command(AudioControlCommand::SetSpeed(self.speed));
+
+        // Played region is sent from its beginning, so the audio thread restarts a looped one from
+        // there
+        let played_region = self.played_region();
+        let pipeline = self.pipeline.as_ref().unwrap();
+        let (skipped, taken) = match played_region {
+            Some((start, end)) => (start, end - start),
+            // Nothing is left after the whole track duration, so it doesn't cut anything
+            None => (position, pipeline.duration()),
+        };
+        self.skipped_from_beg = skipped;
+        let source = match played_region {
+            Some((start, end)) if self.preview_processed => crate::PlayedSource::Processed(
+                pipeline.processed_region(start, end, self.export_options()),
+            ),
+            _ => crate::PlayedSource::original(pipeline.source(), skipped, taken),
+        };
+        self.send_command(AudioControlCommand::SetLoop(self.looped_region().is_some()));
+        if !self.send_command(AudioControlCommand::Play(source)) {
+            return;
+        }
+        if let Some((start, end)) = played_region {
+            if position > start && position < end {
+                self.send_command(AudioControlCommand::Seek(position - start));
+            }
+        }
+        self.playback_status = PlaybackStatus::Playing;
+        self.pending_plays += 1;
+        // Pause or interruption of this playback leaves the position to resume from
+        self.rewound = false;
+    }
+
+    /// Sends the command to the audio thread.
+    ///
+    /// Returns whether the command has been sent. The audio thread is shut down only while the
+    /// application is closing, so the failure is logged and otherwise ignored.
+    ///
+    /// # Parameters
+    ///
+    /// * `command` - the command to send.
+    fn send_command(&self, command: AudioControlCommand) -> bool {
+        match self.audio_thread.as_ref().unwrap().send(command) {
+            Ok(()) => true,
+            Err(error) => {
+                log::warn!("Failed to send command: {}", error);
+                false
+            }
+        }
+    }
+
+    /// Moves the current position on the audio track, continuing playback from there, if playing.
+    ///
+    /// # Parameters
+    ///
+    /// * `position` - the new position on the audio track.
+    fn seek_to(&mut self, position: std::time::Duration) {
+        self.audio_wave_position = position.as_secs_f32();
+        self.rewound = false;
+
+        if let PlaybackStatus::Playing = self.playback_status {
+            let in_played_region = self
+                .played_region()
+                .is_none_or(|(start, end)| position >= start && position < end);
+            if position >= self.skipped_from_beg && in_played_region {
+                // The playing audio source starts at the skipped position, and seek position is
+                // relative to its beginning
+                if self.send_command(AudioControlCommand::Seek(position - self.skipped_from_beg)) {
+                    // Published elapsed time is updated a bit later, so take the exact one, or
+                    // the position would jump back to the one before the seek for a moment
+                    match self.audio_thread.as_ref().unwrap().query_time_elapsed() {
+                        Ok(time_elapsed) => {
+                            self.audio_wave_position =
+                                (self.skipped_from_beg + time_elapsed).as_secs_f32();
+                        }
+                        Err(error) => {
+                            log::warn!("Failed to query elapsed time: {}", error);
+                        }
+                    }
+                }
+            } else {
+                // The playing audio source has no data before the skipped position, or after the
+                // end of the played region
+                self.play_from(position);
+            }
+        }
+    }
+
+    /// Returns the current position on the audio track.
+    ///
+    /// While playing, the position is taken from the audio thread as is, so it is not affected
+    /// by rounding of the audio wave position.
+    fn current_position(&self) -> std::time::Duration {
+        if let PlaybackStatus::Playing = self.playback_status {
+            self.skipped_from_beg + self.audio_thread.as_ref().unwrap().time_elapsed()
+        } else {
+            std::time::Duration::from_secs_f32(self.audio_wave_position)
+        }
+    }
+
+    /// Returns the region chosen by user, if both its bounds are set and valid.
+    fn region(&self) -> Option<(std::time::Duration, std::time::Duration)> {
+        let start = self.region_start?;
+        let end = self.region_end?;
+        let duration = self.pipeline.as_ref()?.duration();
+        (start < end && end <= duration).then_some((start, end))
+    }
+
+    /// Returns the region to play over and over, if looping it is on and the region is valid.
+    fn looped_region(&self) -> Option<(std::time::Duration, std::time::Duration)> {
+        self.region()
+            .filter(|_| self.loop_mode == LoopMode::LoopRegion)
+    }
+
+    /// Returns the region, that bounds playback, if looping it, playing it only or previewing it
+    /// processed is on and the region is valid.
+    fn played_region(&self) -> Option<(std::time::Duration, std::time::Duration)> {
+        self.region().filter(|_| {
+            matches!(
+                self.loop_mode,
+                LoopMode::LoopRegion | LoopMode::PlayRegionOnly
+            ) || self.preview_processed
+        })
+    }
+
+    /// Controls region part of the UI, where user chooses the region for cutting.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing region controls on.
+    fn region_control(&mut self, ui: &mut egui::Ui) {
+        let duration = self.pipeline.as_ref().unwrap().duration();
+        let position = self.current_position().min(duration);
+        let played_region = self.played_region();
+        let looped_region = self.looped_region();
+        let preview_processed = self.preview_processed;
+
+        ui.horizontal(|ui| {
+            ui.label(tr(Key::RegionFrom));
+            region_bound_edit(
+                ui,
+                &mut self.region_start,
+                &mut self.region_start_text,
+                position,
+                duration,
+            );
+            ui.label(tr(Key::RegionTo));
+            region_bound_edit(
+                ui,
+                &mut self.region_end,
+                &mut self.region_end_text,
+                position,
+                duration,
+            );
+
+            if ui.button(tr(Key::Reset)).clicked() {
+                self.region_start = None;
+                self.region_end = None;
+            }
+
+            ui.separator();
+            ui.selectable_value(&mut self.preview_processed, false, tr(Key::PreviewOriginal))
+                .on_hover_text(tr(Key::PreviewOriginalHint));
+            ui.selectable_value(&mut self.preview_processed, true, tr(Key::PreviewProcessed))
+                .on_hover_text(tr(Key::PreviewProcessedHint));
+        });
+
+        // Playing audio source is bounded by the played region and is either original or
+        // processed, so a new one is needed. It continues from the same position
+        if let PlaybackStatus::Playing = self.playback_status {
+            if self.played_region() != played_region
+                || self.looped_region() != looped_region
+                || self.preview_processed != preview_processed
+            {
+                self.play_from(position);
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.fade_in, tr(Key::FadeIn));
+            fade_duration_edit(ui, self.fade_in, &mut self.fade_in_duration);
+            ui.checkbox(&mut self.fade_out, tr(Key::FadeOut));
+            fade_duration_edit(ui, self.fade_out, &mut self.fade_out_duration);
+            ui.checkbox(&mut self.normalize, tr(Key::Normalize))
+                .on_hover_text(tr_args(
+                    Key::NormalizeHint,
+                    &[&crate::export::NORMALIZE_TARGET_DBFS],
+                ));
+            ui.label(tr(Key::Gain));
+            ui.add(
+                egui::DragValue::new(&mut self.gain_db)
+                    .range(-30.0..=30.0)
+                    .speed(0.1)
+                    .fixed_decimals(1)
+                    .suffix(tr(Key::DecibelsSuffix)),
+            )
+            .on_hover_text(tr(Key::GainHint));
+            ui.checkbox(&mut self.limit, tr(Key::Limit))
+                .on_hover_text(tr_args(
+                    Key::LimitHint,
+                    &[&crate::export::LIMIT_CEILING_DBFS],
+                ));
+        });
+
+        export_format_edit(ui, &mut self.export_format, &mut self.channel_mode);
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    self.region().is_some() && self.export_task.is_none(),
+                    egui::Button::new(tr(Key::SaveRegion)),
+                )
+                .clicked()
+            {
+                self.export_region_button_clicked(false);
+            }
+            if ui
+                .add_enabled(
+                    self.region().is_some() && self.export_task.is_none(),
+                    egui::Button::new(tr(Key::SaveWithoutRegion)),
+                )
+                .on_hover_text(tr(Key::SaveWithoutRegionHint))
+                .clicked()
+            {
+                self.export_region_button_clicked(true);
+            }
+        });
+
+        self.export_progress(ui);
+        match &self.export_result {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            None => {}
+        }
+
+        if let Some((start, end)) = self.region() {
+            ui.label(tr_args(
+                Key::RegionDuration,
+                &[&format!("{:.3}", (end - start).as_secs_f64())],
+            ));
+        } else if let (Some(start), Some(end)) = (self.region_start, self.region_end) {
+            if start >= end {
+                ui.colored_label(ui.visuals().error_fg_color, tr(Key::RegionStartAfterEnd));
+            }
+        }
+
+        self.region_list(ui);
+    }
+
+    /// Controls the list of regions for cutting into separate files.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the list on.
+    fn region_list(&mut self, ui: &mut egui::Ui) {
+        let region = self.region();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(region.is_some(), egui::Button::new(tr(Key::AddToList)))
+                .clicked()
+            {
+                insert_region(&mut self.regions, region.unwrap());
+            }
+
+            if ui
+                .add_enabled(
+                    !self.regions.is_empty() && self.export_task.is_none(),
+                    egui::Button::new(tr(Key::SaveAllRegions)),
+                )
+                .clicked()
+            {
+                self.export_all_regions_button_clicked();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.regions.is_empty() && self.export_task.is_none(),
+                    egui::Button::new(tr(Key::SaveJoinedRegions)),
+                )
+                .clicked()
+            {
+                self.export_joined_regions_button_clicked();
+            }
+            // Regions can't overlap and be separated at once
+            if ui
+                .checkbox(&mut self.crossfade, tr(Key::Crossfade))
+                .on_hover_text(tr(Key::CrossfadeHint))
+                .changed()
+                && self.crossfade
+            {
+                self.gap = false;
+            }
+            fade_duration_edit(ui, self.crossfade, &mut self.crossfade_duration);
+            if ui
+                .checkbox(&mut self.gap, tr(Key::Silence))
+                .on_hover_text(tr(Key::SilenceHint))
+                .changed()
+                && self.gap
+            {
+                self.crossfade = false;
+            }
+            fade_duration_edit(ui, self.gap, &mut self.gap_duration);
+        });
+
+        ui.horizontal(|ui| {
+            if ui
+                .button(tr(Key::SplitAtSilences))
+                .on_hover_text(tr(Key::SplitAtSilencesHint))
+                .clicked()
+            {
+                self.split_at_silences_button_clicked();
+            }
+            ui.label(tr(Key::SilenceThreshold));
+            ui.add(
+                egui::DragValue::new(&mut self.silence_threshold_db)
+                    .range(-80.0..=-10.0)
+                    .speed(0.5)
+                    .suffix(tr(Key::DecibelsSuffix)),
+            )
+            .on_hover_text(tr(Key::SilenceThresholdHint));
+            ui.label(tr(Key::MinSilence));
+            let mut duration_ms = self.min_silence_duration.as_millis() as u64;
+            if ui
+                .add(
+                    egui::DragValue::new(&mut duration_ms)
+                        .range(50..=10000)
+                        .suffix(tr(Key::MillisecondsSuffix)),
+                )
+                .on_hover_text(tr(Key::MinSilenceHint))
+                .changed()
+            {
+                self.min_silence_duration = std::time::Duration::from_millis(duration_ms);
+            }
+        });
+
+        let mut edited_region = None;
+        for (index, &(start, end)) in self.regions.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(tr_args(
+                    Key::RegionListItem,
+                    &[
+                        &(index + 1),
+                        &format!("{:.3}", start.as_secs_f64()),
+                        &format!("{:.3}", end.as_secs_f64()),
+                    ],
+                ));
+                if ui
+                    .button(tr(Key::Edit))
+                    .on_hover_text(tr(Key::EditHint))
+                    .clicked()
+                {
+                    edited_region = Some((index, true));
+                }
+                if ui.button("✖").on_hover_text(tr(Key::RemoveHint)).clicked() {
+                    edited_region = Some((index, false));
+                }
+            });
+        }
+
+        if let Some((index, edit)) = edited_region {
+            let (start, end) = self.regions.remove(index);
+            if edit {
+                self.region_start = Some(start);
+                self.region_end = Some(end);
+            }
+        }
+    }
+
+    /// Replaces the listed regions with the sounding segments of the audio track, separated by
+    /// silences, as chosen by user.
+    fn split_at_silences_button_clicked(&mut self) {
+        let threshold = 10.0_f32.powf(self.silence_threshold_db / 20.0);
+        let min_silence = self.min_silence_duration;
+        // Scanning the whole audio track may take a while
+        self.schedule_busy_task(move |app| {
+            let pipeline = app.pipeline.as_ref().unwrap();
+            let segments = pipeline.find_sound_segments(threshold, min_silence);
+            if segments.is_empty() {
+                app.export_result = Some(Err(String::from(tr(Key::NoSoundFound))));
+            } else {
+                app.export_result = None;
+                // The list is replaced at once, so it is undone at once too
+                app.regions = segments;
+            }
+        });
+    }
+
+    /// Returns the current state of the regions, chosen by user.
+    fn region_state(&self) -> RegionState {
+        RegionState {
+            start: self.region_start,
+            end: self.region_end,
+            regions: self.regions.clone(),
+        }
+    }
+
+    /// Replaces the regions, chosen by user, with the given ones.
+    ///
+    /// # Parameters
+    ///
+    /// * `state` - the regions to set.
+    fn set_region_state(&mut self, state: RegionState) {
+        self.region_start = state.start;
+        self.region_end = state.end;
+        self.regions = state.regions;
+        self.recorded_region_state = self.region_state();
+    }
+
+    /// Records the change of the regions since the last record to the undo history.
+    ///
+    /// Changes are not recorded while mouse button is held, so dragging a region bound is undone
+    /// at once, not step by step.
+    ///
+    /// # Parameters
+    ///
+    /// * `ctx` - UI context for checking the mouse buttons.
+    fn record_region_change(&mut self, ctx: &egui::Context) {
+        let state = self.region_state();
+        if state == self.recorded_region_state || ctx.input(|i| i.pointer.any_down()) {
+            return;
+        }
+
+        let previous_state = std::mem::replace(&mut self.recorded_region_state, state);
+        self.undo_stack.push(previous_state);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Reverts the last recorded change of the regions.
+    fn undo_region_change(&mut self) {
+        if let Some(state) = self.undo_stack.pop() {
+            self.redo_stack.push(self.region_state());
+            self.set_region_state(state);
+        }
+    }
+
+    /// Applies again the last reverted change of the regions.
+    fn redo_region_change(&mut self) {
+        if let Some(state) = self.redo_stack.pop() {
+            self.undo_stack.push(self.region_state());
+            self.set_region_state(state);
+        }
+    }
+
+    /// Returns processing and format of the exported regions, as chosen by user.
+    fn export_options(&self) -> pipeline::ExportOptions {
+        pipeline::ExportOptions {
+            fades: pipeline::Fades {
+                fade_in: if self.fade_in {
+                    self.fade_in_duration
+                } else {
+                    std::time::Duration::ZERO
+                },
+                fade_out: if self.fade_out {
+                    self.fade_out_duration
+                } else {
+                    std::time::Duration::ZERO
+                },
+            },
+            normalize: self.normalize,
+            gain_db: self.gain_db,
+            limit: self.limit,
+            channel_mode: self.channel_mode,
+            format: self.export_format,
+        }
+    }
+
+    /// Asks user for the directory to save all listed regions to and schedules the export.
+    ///
+    /// Each region is written to a separate file, numbered in the order of the list.
+    fn export_all_regions_button_clicked(&mut self) {
+        let source_file = self.current_file_name.as_ref().unwrap();
+        let file_stem = source_file
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+
+        let mut dialog = rfd::FileDialog::new();
+        if let Some(directory) = source_file.parent() {
+            dialog = dialog.set_directory(directory);
+        }
+
+        if let Some(directory) = dialog.pick_folder() {
+            let source_file = source_file.clone();
+            let regions = self.regions.clone();
+            let options = self.export_options();
+            self.export_in_background(move |pipeline, progress| {
+                regions
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(index, &(start, end))| {
+                        let file = directory.join(format!(
+                            "{}_{}_{:02}.{}",
+                            file_stem,
+                            tr(Key::RegionFileName),
+                            index + 1,
+                            options.format.extension()
+                        ));
+                        crate::export::check_destination(&source_file, &file)?;
+                        pipeline.export_region(
+                            start,
+                            end,
+                            options,
+                            &file,
+                            &progress.part(index, regions.len()),
+                        )
+                    })
+                    .map(|_| {
+                        tr_args(
+                            Key::RegionsSaved,
+                            &[&regions.len(), &directory.to_string_lossy()],
+                        )
+                    })
+                    .map_err(|error| tr_args(Key::RegionsSaveFailed, &[&error]))
+            });
+        }
+    }
+
+    /// Asks user for the file to save all listed regions, joined one after another, to and
+    /// schedules the export.
+    fn export_joined_regions_button_clicked(&mut self) {
+        let source_file = self.current_file_name.as_ref().unwrap();
+        let format = self.export_format;
+        let file_name = format!(
+            "{}_{}.{}",
+            source_file.file_stem().unwrap().to_string_lossy(),
+            tr(Key::JoinedFileName),
+            format.extension()
+        );
+
+        let filter_name = tr_args(Key::FileFilter, &[&format.extension().to_uppercase()]);
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter(filter_name, &[format.extension()])
+            .set_file_name(file_name);
+        if let Some(directory) = source_file.parent() {
+            dialog = dialog.set_directory(directory);
+        }
+
+        if let Some(file) = dialog.save_file() {
+            let regions = self.regions.clone();
+            let transition = if self.gap {
+                pipeline::Transition::Silence(self.gap_duration)
+            } else if self.crossfade {
+                pipeline::Transition::Crossfade(self.crossfade_duration)
+            } else {
+                pipeline::Transition::Crossfade(std::time::Duration::ZERO)
+            };
+            let options = self.export_options();
+            let source_file = source_file.clone();
+            self.export_in_background(move |pipeline, progress| {
+                crate::export::check_destination(&source_file, &file)
+                    .and_then(|_| {
+                        pipeline.export_joined(&regions, transition, options, &file, progress)
+                    })
+                    .map(|_| tr_args(Key::JoinedRegionsSaved, &[&file.to_string_lossy()]))
+                    .map_err(|error| tr_args(Key::RegionsSaveFailed, &[&error]))
+            });
+        }
+    }
+
+    /// Asks user for the file to save the chosen region, or the audio track without it, to and
+    /// schedules the export.
+    ///
+    /// # Parameters
+    ///
+    /// * `cut_out` - whether to save the audio track without the region, instead of the region.
+    fn export_region_button_clicked(&mut self, cut_out: bool) {
+        let source_file = self.current_file_name.as_ref().unwrap();
+        let format = self.export_format;
+        let file_name = format!(
+            "{}_{}.{}",
+            source_file.file_stem().unwrap().to_string_lossy(),
+            tr(if cut_out {
+                Key::CutOutFileName
+            } else {
+                Key::RegionFileName
+            }),
+            format.extension()
+        );
+
+        let filter_name = tr_args(Key::FileFilter, &[&format.extension().to_uppercase()]);
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter(filter_name, &[format.extension()])
+            .set_file_name(file_name);
+        if let Some(directory) = source_file.parent() {
+            dialog = dialog.set_directory(directory);
+        }
+
+        if let Some(file) = dialog.save_file() {
+            let (start, end) = self.region().unwrap();
+            let options = self.export_options();
+            let source_file = source_file.clone();
+            self.export_in_background(move |pipeline, progress| {
+                let saved = if cut_out {
+                    Key::CutOutSaved
+                } else {
+                    Key::RegionSaved
+                };
+                crate::export::check_destination(&source_file, &file)
+                    .and_then(|_| {
+                        if cut_out {
+                            pipeline.export_cut_out(
+                                start,
+                                end,
+                                CUT_OUT_CROSSFADE_DURATION,
+                                options,
+                                &file,
+                                progress,
+                            )
+                        } else {
+                            pipeline.export_region(start, end, options, &file, progress)
+                        }
+                    })
+                    .map(|_| tr_args(saved, &[&file.to_string_lossy()]))
+                    .map_err(|error| tr_args(Key::RegionSaveFailed, &[&error]))
+            });
+        }
+    }
+
+    /// Runs a region export of the current audio track in the background, so UI keeps responding
+    /// and shows its progress, see [Self::export_progress].
+    ///
+    /// The export thread is not waited for on exit. Files are replaced only once they are written
+    /// completely, see [crate::export::write], so an interrupted export leaves no truncated files.
+    ///
+    /// # Parameters
+    ///
+    /// * `task` - the export, that reports its progress and returns the message about the
+    ///   written files or the error message.
+    fn export_in_background(
+        &mut self,
+        task: impl FnOnce(&AudioPipeline, &ExportProgress) -> Result<String, String> + Send + 'static,
+    ) {
+        let pipeline = self.pipeline.clone().unwrap();
+        let progress = ExportProgress::default();
+        let task_progress = progress.clone();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            // UI may have dropped the receiver along with the audio track, nothing to report then
+            let _ = sender.send(task(&pipeline, &task_progress));
+        });
+        self.export_task = Some((progress, receiver));
+        self.export_result = None;
+    }
+
+    /// Shows progress of the region export, running in the background, and takes its outcome,
+    /// once it is finished.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the progress bar on.
+    fn export_progress(&mut self, ui: &mut egui::Ui) {
+        let Some((progress, receiver)) = &self.export_task else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.export_result = Some(result);
+                self.export_task = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {
+                ui.add(egui::ProgressBar::new(progress.get()).show_percentage());
+                // Progress is updated by another thread, so nothing else requests a repaint
+                ui.ctx().request_repaint();
+            }
+            // The export thread has panicked
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.export_result = Some(Err(String::from(tr(Key::ExportInterrupted))));
+                self.export_task = None;
+            }
+        }
+    }
+
+    /// Controls buttons, that copy the current position as a frame or a sample index.
+    ///
+    /// Frame index counts multichannel frames, i.e. one per sample rate tick, while sample index
+    /// counts interleaved samples of all channels, so it is frame index multiplied by channels.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the buttons on.
+    fn copy_position_buttons(&self, ui: &mut egui::Ui) {
+        let pipeline = self.pipeline.as_ref().unwrap();
+        let frame = pipeline::frame_index(self.current_position(), pipeline.sample_rate());
+
+        ui.horizontal(|ui| {
+            if ui.button(tr(Key::CopyFrameIndex)).clicked() {
+                ui.ctx().copy_text(frame.to_string());
+            }
+            if ui.button(tr(Key::CopySampleIndex)).clicked() {
+                ui.ctx()
+                    .copy_text((frame * u64::from(pipeline.channels())).to_string());
+            }
+        });
+    }
+
+    /// Updates the playback status from the ones, reported by the audio thread.
+    ///
+    /// If the audio thread stops on its own, the track has been played to its end, so it is
+    /// restarted from the beginning if repeating is on, otherwise playback is stopped.
+    fn handle_playback_status(&mut self) {
+        while let Some(status) = self.audio_thread.as_ref().unwrap().try_recv_status() {
+            if self.pending_plays > 0 {
+                if let PlaybackStatus::Playing = status {
+                    self.pending_plays -= 1;
+                }
+                continue;
+            }
+
+            match (self.playback_status, status) {
+                (PlaybackStatus::Playing, PlaybackStatus::Stopped) => {
+                    if self.loop_mode == LoopMode::RepeatTrack {
+                        self.play_from(std::time::Duration::ZERO);
+                    } else {
+                        // Played region is likely auditioned again, so get back to its beginning
+                        let start = self
+                            .played_region()
+                            .map_or(std::time::Duration::ZERO, |(start, _)| start);
+                        self.playback_status = PlaybackStatus::Stopped;
+                        self.audio_wave_position = start.as_secs_f32();
+                        self.skipped_from_beg = std::time::Duration::ZERO;
+                        self.rewound = true;
+                    }
+                }
+                (_, status) => self.playback_status = status,
+            }
+        }
+    }
+
+    /// Remembers the current position on the audio wave as the last pause position, unless it is
+    /// the very beginning of the audio track.
+    fn remember_pause_position(&mut self) {
+        if self.audio_wave_position > 0.0 {
+            self.last_pause_position = Some(self.audio_wave_position);
+        }
+    }
+
+    /// Stops playback and moves the current position to the beginning of the audio track.
+    fn stop(&mut self) {
+        self.send_command(AudioControlCommand::Stop);
+        self.remember_pause_position();
+        self.playback_status = PlaybackStatus::Stopped;
+        self.audio_wave_position = 0.0;
+        self.skipped_from_beg = std::time::Duration::ZERO;
+        self.rewound = true;
+    }
+
+    /// Moves the current position to [END_JUMP_LEAD] before the end of the audio track.
+    fn jump_to_end(&mut self) {
+        let duration = self.pipeline.as_ref().unwrap().duration();
+        self.seek_to(duration.saturating_sub(END_JUMP_LEAD));
+    }
+
+    /// Pauses playback if playing, otherwise starts playback from the current position, or from
+    /// the beginning of the chosen region, if playback has been stopped.
+    fn toggle_playback(&mut self) {
+        match self.playback_status {
+            PlaybackStatus::Playing => {
+                // Pause is the same as stop, but we don't clear audio wave position and
+                // skipped duration immediately.
+                //
+                // In context of AudioThread, we don't differ stop and pause, as user can
+                // change start time, so we have to send a new audio source to the
+                // AudioThread each time.
+                self.send_command(AudioControlCommand::Stop);
+                self.remember_pause_position();
+                self.playback_status = PlaybackStatus::Stopped;
+            }
+            PlaybackStatus::Stopped | PlaybackStatus::Interrupted => {
+                // Start over from what user has selected, instead of the track beginning
+                let position = match self.region() {
+                    Some((start, _)) if self.rewound => start,
+                    _ => std::time::Duration::from_secs_f32(self.audio_wave_position),
+                };
+                self.audio_wave_position = position.as_secs_f32();
+                self.play_from(position);
+            }
+        }
+    }
+
+    /// Handles keyboard shortcuts for playback control: space plays or pauses, S stops, and
+    /// arrows move the current position by [SEEK_STEP] back and forth, while Home and End move it
+    /// to the beginning and the end of the audio track. I and O set the region
+    /// beginning and end to the current position. Also Ctrl+Z undoes region changes, while Ctrl+Y
+    /// and Ctrl+Shift+Z redo them.
+    ///
+    /// # Parameters
+    ///
+    /// * `ctx` - UI context for reading the pressed keys from.
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        // Focused widget handles keys on its own: text fields type them, buttons are clicked
+        // with space, and sliders are moved with arrows
+        if ctx.memory(|memory| memory.focused().is_some()) {
+            return;
+        }
+
+        // Shortcut with more modifiers goes first, as the one with fewer matches it as well
+        let redo_shortcut = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::Z,
+        );
+        let undo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+        let redo_y_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+        let (undo, redo) = ctx.input_mut(|i| {
+            let redo = i.consume_shortcut(&redo_shortcut) || i.consume_shortcut(&redo_y_shortcut);
+            (i.consume_shortcut(&undo_shortcut), redo)
+        });
+        if undo {
+            self.undo_region_change();
+        }
+        if redo {
+            self.redo_region_change();
+        }
+
+        let (play_pause, stop, back, forward, mark_start, mark_end) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::Space),
+                i.key_pressed(egui::Key::S),
+                i.key_pressed(egui::Key::ArrowLeft),
+                i.key_pressed(egui::Key::ArrowRight),
+                i.key_pressed(egui::Key::I),
+                i.key_pressed(egui::Key::O),
+            )
+        });
+        let (home, end) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::Home),
+                i.key_pressed(egui::Key::End),
+            )
+        });
+
+        if play_pause {
+            self.toggle_playback();
+        }
+        if stop {
+            self.stop();
+        }
+        if back || forward {
+            let duration = self.pipeline.as_ref().unwrap().duration();
+            let position = self.current_position();
+            let position = if forward {
+                (position + SEEK_STEP).min(duration)
+            } else {
+                position.saturating_sub(SEEK_STEP)
+            };
+            self.seek_to(position);
+        }
+        if home {
+            self.seek_to(std::time::Duration::ZERO);
+        }
+        if end {
+            self.jump_to_end();
+        }
+        if mark_start || mark_end {
+            self.mark_region_bound(mark_start);
+        }
+    }
+
+    /// Sets the region bound to the current position, so user can mark the region while listening.
+    ///
+    /// # Parameters
+    ///
+    /// * `start` - whether to set the region beginning, or its end otherwise.
+    fn mark_region_bound(&mut self, start: bool) {
+        let duration = self.pipeline.as_ref().unwrap().duration();
+        let position = self.current_position().min(duration);
+        let played_region = self.played_region();
+
+        if start {
+            self.region_start = Some(position);
+        } else {
+            self.region_end = Some(position);
+        }
+        // Bound, marked past the other one, makes it the opposite one
+        if let (Some(start), Some(end)) = (self.region_start, self.region_end) {
+            if end < start {
+                self.region_start = Some(end);
+                self.region_end = Some(start);
+            }
+        }
+
+        // Playing audio source is bounded by the played region, so a new one is needed
+        if self.played_region() != played_region {
+            if let PlaybackStatus::Playing = self.playback_status {
+                self.play_from(position);
+            }
+        }
+    }
+
+    /// Controls audio playback part of the UI.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing audio playback controls on.
+    fn playback_control(&mut self, ui: &mut egui::Ui) {
+        let action = if let PlaybackStatus::Playing = self.playback_status {
+            tr(Key::Pause)
+        } else {
+            tr(Key::Play)
+        };
+
+        ui.horizontal(|ui| {
+            // Trying to center buttons two buttons in the same row at the center
+            // horizontally, when already horizontally center other widgets from top to down
+            // might be a problem for immediate mode UI:
+            // https://github.com/emilk/egui/discussions/2916
+            // Rude fix is to add some constant space before buttons or need to change the
+            // UI layout (better)
+            // let button_width = 40.0;
+            // let total_width = button_width * 2.0;
+            // ui.add_space((ui.available_width() - total_width) / 2.0);
+            // Button in the widget for stopping
+            if ui.button(tr(Key::Stop)).clicked() {
+                self.stop();
+            }
+
+            if ui.button("⏮").on_hover_text(tr(Key::JumpToStart)).clicked() {
+                self.seek_to(std::time::Duration::ZERO);
+            }
+
+            // Button in the widget for playing and pausing
+            if ui.button(action).clicked() {
+                self.toggle_playback();
+            }
+
+            if ui.button("⏭").on_hover_text(tr(Key::JumpToEnd)).clicked() {
+                self.jump_to_end();
+            }
+
+            ui.separator();
+            ui.selectable_value(&mut self.loop_mode, LoopMode::Off, tr(Key::PlayOnce));
+            ui.selectable_value(
+                &mut self.loop_mode,
+                LoopMode::RepeatTrack,
+                tr(Key::RepeatTrack),
+            );
+            ui.selectable_value(
+                &mut self.loop_mode,
+                LoopMode::LoopRegion,
+                tr(Key::LoopRegion),
+            )
+            .on_hover_text(tr(Key::LoopRegionHint));
+            ui.selectable_value(
+                &mut self.loop_mode,
+                LoopMode::PlayRegionOnly,
+                tr(Key::PlayRegionOnly),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(tr(Key::Volume));
+            let (mute_icon, mute_hint) = if self.muted {
+                ("🔇", tr(Key::Unmute))
+            } else {
+                ("🔊", tr(Key::Mute))
+            };
+            if ui.button(mute_icon).on_hover_text(mute_hint).clicked() {
+                self.muted = !self.muted;
+                self.send_command(AudioControlCommand::SetMuted(self.muted));
+            }
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.volume, 0.0..=2.0)
+                        .custom_formatter(|volume, _| format!("{:.0}%", volume * 100.0)),
+                )
+                .changed()
+            {
+                self.send_command(AudioControlCommand::SetVolume(self.volume));
+            }
+
+            ui.label(tr(Key::Speed));
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.speed, 0.5..=2.0)
+                        .custom_formatter(|speed, _| format!("{:.2}x", speed)),
+                )
+                .changed()
+            {
+                self.send_command(AudioControlCommand::SetSpeed(self.speed));
+            }
+        });
+
+        self.level_meter(ui);
+    }
+
+    /// Controls the level meter, that shows the peak level of the playing audio with the playback
+    /// volume applied.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the meter on.
+    fn level_meter(&self, ui: &mut egui::Ui) {
+        let level = match self.playback_status {
+            PlaybackStatus::Playing if !self.muted => {
+                self.audio_thread.as_ref().unwrap().peak_level() * self.volume
+            }
+            _ => 0.0,
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(tr(Key::Level));
+            // Volume above the original one may clip the sound, so let user notice it
+            let color = if level > 1.0 {
+                ui.visuals().error_fg_color
+            } else {
+                egui::Color32::from_rgb(87, 168, 50)
+            };
+            ui.add(
+                egui::ProgressBar::new(level.min(1.0))
+                    .fill(color)
+                    .desired_height(8.0),
+            );
+        });
+    }
+
+    /// Shows the bottom status bar with the audio sink state, published by the audio thread.
+    ///
+    /// # Parameters
+    ///
+    /// * `ctx` - UI context for placing the status bar panel on.
+    fn sink_status_bar(&self, ctx: &egui::Context) {
+        let status = self.audio_thread.as_ref().unwrap().sink_status();
+
+        let state = if status.empty {
+            tr(Key::SinkEmpty)
+        } else if status.paused {
+            tr(Key::SinkPaused)
+        } else {
+            tr(Key::SinkPlaying)
+        };
+
+        egui::TopBottomPanel::bottom("sink_status").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr_args(Key::SinkState, &[&state]));
+                ui.separator();
+                ui.label(tr_args(
+                    Key::SinkPosition,
+                    &[&format!("{:.1}", status.position.as_secs_f32())],
+                ));
+                ui.separator();
+                ui.label(tr_args(Key::SinkQueued, &[&status.queued]));
+                ui.separator();
+                ui.label(tr_args(
+                    Key::SinkVolume,
+                    &[&format!("{:.2}", status.volume)],
+                ));
+            });
+        });
+    }
+
+    /// Controls application settings part of the UI.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the settings on.
+    fn settings(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(tr(Key::Settings)).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr(Key::Language));
+                let mut lang = i18n::lang();
+                egui::ComboBox::from_id_salt("language")
+                    .selected_text(lang.name())
+                    .show_ui(ui, |ui| {
+                        for choice in Lang::ALL {
+                            ui.selectable_value(&mut lang, choice, choice.name());
+                        }
+                    });
+                i18n::set_lang(lang);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr(Key::UiScale));
+                let mut scale_percent = (self.ui_scale * 100.0).round() as u32;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut scale_percent)
+                            .range(50..=300)
+                            .speed(1.0)
+                            .suffix("%"),
+                    )
+                    .on_hover_text(tr(Key::UiScaleHint))
+                    .changed()
+                {
+                    // Applied from the next frame, so the current one is laid out consistently
+                    ui.ctx().set_zoom_factor(scale_percent as f32 / 100.0);
+                }
+            });
 
-    /// AudioThread controls separate thread that performs audio playback process.
-    audio_thread: Option<audio_thread::AudioThread>,
+            ui.horizontal(|ui| {
+                ui.label(tr(Key::LoadMode));
+                let name = |mode| match mode {
+                    pipeline::LoadMode::Auto => tr(Key::LoadModeAuto),
+                    pipeline::LoadMode::Memory => tr(Key::LoadModeMemory),
+                    pipeline::LoadMode::Streamed => tr(Key::LoadModeStreamed),
+                };
+                egui::ComboBox::from_id_salt("load_mode")
+                    .selected_text(name(self.load_mode))
+                    .show_ui(ui, |ui| {
+                        for mode in [
+                            pipeline::LoadMode::Auto,
+                            pipeline::LoadMode::Memory,
+                            pipeline::LoadMode::Streamed,
+                        ] {
+                            ui.selectable_value(&mut self.load_mode, mode, name(mode));
+                        }
+                    })
+                    .response
+                    .on_hover_text(tr_args(
+                        Key::LoadModeHint,
+                        &[&(pipeline::STREAMING_FILE_SIZE / 1024 / 1024)],
+                    ));
+            });
 
-    /// Current audio track filename, chosen by user.
-    current_file_name: Option<std::path::PathBuf>,
-    /// Audio source that corresponds to the current audio track.
-    audio_source: Option<crate::AudioSourceBuf>,
-    samples: Vec<f32>,
-    max_sample: f32,
-    /// Duration of the part of the audio_source, that user skips before sending an audio source
-    /// to the AudioThread.
-    skipped_from_beg: std::time::Duration,
-    /// Position of the currently chosen or playing sample on the audio wave.
-    audio_wave_position: f32,
-}
+            ui.checkbox(&mut self.show_sink_status, tr(Key::DebugInfo));
 
-impl AudioCutterApp {
-    /// Loads audio source of the current audio track.
+            ui.horizontal(|ui| {
+                ui.label(tr(Key::UpdateInterval));
+                let mut interval_ms = self.update_interval.as_millis() as u64;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut interval_ms)
+                            .range(15..=1000)
+                            .suffix(tr(Key::MillisecondsSuffix)),
+                    )
+                    .on_hover_text(tr(Key::UpdateIntervalHint))
+                    .changed()
+                {
+                    self.update_interval = std::time::Duration::from_millis(interval_ms);
+                    self.send_command(AudioControlCommand::SetUpdateInterval(self.update_interval));
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr(Key::HiddenUpdateInterval));
+                let mut interval_ms = self.hidden_update_interval.as_millis() as u64;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut interval_ms)
+                            .range(100..=5000)
+                            .suffix(tr(Key::MillisecondsSuffix)),
+                    )
+                    .on_hover_text(tr(Key::HiddenUpdateIntervalHint))
+                    .changed()
+                {
+                    self.hidden_update_interval = std::time::Duration::from_millis(interval_ms);
+                    self.send_command(AudioControlCommand::SetHiddenUpdateInterval(
+                        self.hidden_update_interval,
+                    ));
+                }
+            });
+        });
+    }
+
+    // TODO: use epaint as dancing strings demo?
+    // TODO: look at https://github.com/Cannedfood/egui-audio/tree/main
+
+    fn paint_sound_wave(&mut self, ui: &mut egui::Ui) {
+        if self.pipeline.is_none() {
+            return;
+        }
+
+        self.wave_zoom_control(ui);
+
+        Frame::window(ui.style()).show(ui, |ui| {
+            ui.ctx().request_repaint();
+
+            // Desired size of the frame with sound wave: occupy all available width (x-coordinate)
+            // and use 25% of the width as height (y-coordinate)
+            let desired_size = ui.available_width() * egui::vec2(1.0, 0.25);
+            let (frame_rect, response) =
+                ui.allocate_exact_size(desired_size, egui::Sense::click_and_drag());
+            // Zoomed in sound wave and the region over it go beyond the frame
+            ui.set_clip_rect(frame_rect.intersect(ui.clip_rect()));
+
+            let duration = self.pipeline.as_ref().unwrap().duration().as_secs_f32();
+            self.zoom_wave_with_pointer(ui, &response, frame_rect, duration);
+            self.follow_playback_on_wave(duration);
+            let view_start = self.wave_view_start;
+            let view_end = view_start + duration / self.wave_zoom;
+
+            // Linear transformation from the rectangle with the visible part of the sound wave,
+            // where X is seconds and Y is sample value, to frame rectangle. Egui uses a coordinate
+            // system, where Y increases downwards, so the Y range is inverted to draw positive
+            // samples at the top
+            // Silent track has no amplitude, so keep some range for the transformation anyway
+            let amplitude = self.waveform.max_amplitude().max(f32::EPSILON);
+            let to_screen = emath::RectTransform::from_to(
+                egui::Rect::from_x_y_ranges(view_start..=view_end, amplitude..=-amplitude),
+                frame_rect,
+            );
+
+            if self.show_spectrogram {
+                self.paint_spectrogram(ui, frame_rect, to_screen, duration);
+            } else {
+                self.paint_waveform(ui, frame_rect, to_screen, duration);
+            }
+
+            self.select_region_on_wave(ui, &response, to_screen);
+
+            if let Some(pause_position) = self.last_pause_position {
+                self.paint_pause_marker(ui, &response, to_screen, pause_position);
+            }
+        });
+    }
+
+    /// Paints the visible part of the sound wave.
     ///
-    /// # Panics
+    /// # Parameters
     ///
-    /// Panics if there is no current audio track, i.e. `current_file_name` is [None].
-    fn load_audio_source(&mut self) {
-        println!(
-            "[Audio Cutter App] Loading audio source: {}...",
-            self.current_file_name.as_ref().unwrap().display()
-        );
-        let file = File::open(self.current_file_name.as_ref().unwrap()).unwrap();
+    /// * `ui` - `egui::UI` for painting.
+    /// * `frame_rect` - rectangle of the frame, the sound wave is painted in.
+    /// * `to_screen` - transformation from seconds and sample values to the frame rectangle.
+    /// * `duration` - duration of the audio track in seconds.
+    fn paint_waveform(
+        &self,
+        ui: &mut egui::Ui,
+        frame_rect: egui::Rect,
+        to_screen: emath::RectTransform,
+        duration: f32,
+    ) {
+        let view_start = to_screen.from().min.x;
+        let view_end = to_screen.from().max.x;
+        let peaks = self.waveform.peaks();
+        let column_duration = duration / peaks.len().max(1) as f32;
+        let first_column = (view_start / column_duration).floor() as usize;
+        let last_column = (view_end / column_duration).ceil() as usize + 1;
+        // Merge columns, so there are no more of them than pixels, and widen the columns, when
+        // zoomed in, so there are no gaps between them
+        let reduced_peaks = self
+            .waveform
+            .reduced_peaks(first_column..last_column, frame_rect.width() as usize);
+        let pixels_per_column = frame_rect.width() / (view_end - view_start) * column_duration;
+        let mut columns = vec![];
+
+        for (column, (lowest, highest)) in reduced_peaks {
+            let time = column as f32 * column_duration;
+            let points = [
+                to_screen * egui::pos2(time, highest),
+                to_screen * egui::pos2(time, lowest),
+            ];
+
+            let thickness = pixels_per_column.max(1.0);
+            let stroke = if time < self.audio_wave_position {
+                // The column is before the current position, so it is green as "completed"
+                egui::Stroke::new(thickness, egui::Color32::from_rgb(87, 168, 50))
+            } else {
+                egui::Stroke::new(thickness, egui::Color32::from_rgb(168, 64, 50))
+            };
 
-        // TODO: Handle decoding error
-        let audio_source = rodio::Decoder::new(std::io::BufReader::new(file)).unwrap();
-        self.audio_source = Option::from(audio_source.buffered());
+            columns.push(epaint::Shape::line_segment(points, stroke));
+        }
+
+        ui.painter().extend(columns);
     }
 
-    fn load_samples(&mut self) {
-        // Number of samples per second
-        let rate = self.audio_source.as_ref().unwrap().sample_rate();
-        println!("[Audio Cutter App] Samples rate: {}", rate);
+    /// Paints the visible part of the spectrogram, starting its computation, if it isn't
+    /// computed yet.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for painting.
+    /// * `frame_rect` - rectangle of the frame, the spectrogram is painted in.
+    /// * `to_screen` - transformation from seconds to X coordinates of the frame rectangle.
+    /// * `duration` - duration of the audio track in seconds.
+    fn paint_spectrogram(
+        &mut self,
+        ui: &mut egui::Ui,
+        frame_rect: egui::Rect,
+        to_screen: emath::RectTransform,
+        duration: f32,
+    ) {
+        if let Some(spectrogram) = self
+            .spectrogram_receiver
+            .as_ref()
+            .and_then(|receiver| receiver.try_recv().ok())
+        {
+            self.spectrogram = Some(spectrogram);
+            self.spectrogram_receiver = None;
+        }
+
+        let Some(spectrogram) = &self.spectrogram else {
+            // Spectrogram takes a while to compute, so it is computed only once it is shown, and
+            // without blocking the UI
+            if self.spectrogram_receiver.is_none() {
+                self.spectrogram_receiver = Some(
+                    self.pipeline
+                        .as_ref()
+                        .unwrap()
+                        .compute_spectrogram_in_background(),
+                );
+            }
+            ui.painter().text(
+                frame_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                tr(Key::ComputingSpectrogram),
+                egui::FontId::default(),
+                ui.visuals().text_color(),
+            );
+            return;
+        };
+
+        let view_start = to_screen.from().min.x;
+        let view_end = to_screen.from().max.x;
+        let column_duration = duration / spectrogram.columns().max(1) as f32;
+        let first_column = (view_start / column_duration).floor() as usize;
+        let last_column = ((view_end / column_duration).ceil() as usize).min(spectrogram.columns());
+        let columns = first_column.min(last_column)..last_column;
+        let width = frame_rect.width() as usize;
 
-        println!(
-            "[Audio Cutter App] Channels: {}",
-            self.audio_source.as_ref().unwrap().channels()
+        // Image is made and uploaded again only when the visible part of the spectrogram changes,
+        // e.g. it is zoomed or scrolled
+        let up_to_date = matches!(
+            &self.spectrogram_texture,
+            Some((texture_columns, texture_width, _))
+                if *texture_columns == columns && *texture_width == width
         );
+        if !up_to_date {
+            let image = spectrogram.image(columns.clone(), width);
+            let texture = ui
+                .ctx()
+                .load_texture("spectrogram", image, egui::TextureOptions::LINEAR);
+            self.spectrogram_texture = Some((columns.clone(), width, texture));
+        }
+        let (_, _, texture) = self.spectrogram_texture.as_ref().unwrap();
 
-        let samples = self.audio_source.as_ref().unwrap().clone();
-        let samples: Vec<f32> = samples.collect();
-        println!("[Audio Cutter App] Samples length: {}", samples.len());
+        let image_rect = egui::Rect::from_x_y_ranges(
+            (to_screen * egui::pos2(columns.start as f32 * column_duration, 0.0)).x
+                ..=(to_screen * egui::pos2(columns.end as f32 * column_duration, 0.0)).x,
+            frame_rect.y_range(),
+        );
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        ui.painter()
+            .image(texture.id(), image_rect, uv, egui::Color32::WHITE);
 
-        // We take only positive samples for simpler sound wave
-        let samples: Vec<f32> = samples.into_iter().filter(|&s| s >= 0.0).collect();
+        // Spectrogram colors leave no room to mark the completed part, so mark the current
+        // position with a line
+        let position_x = (to_screen * egui::pos2(self.audio_wave_position, 0.0)).x;
+        ui.painter().vline(
+            position_x,
+            frame_rect.y_range(),
+            egui::Stroke::new(1.0, egui::Color32::WHITE),
+        );
+    }
 
-        // Fold samples to only such a number of values, that corresponds to seconds
-        let samples: Vec<f32> = samples
-            .chunks(rate as usize)
-            .map(|chunk| chunk.iter().fold(0.0, |acc, x| acc + x))
-            .collect();
+    /// Controls buttons, that zoom the sound wave in and out around its middle, and switch between
+    /// the sound wave and the spectrogram.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` for placing the buttons on.
+    fn wave_zoom_control(&mut self, ui: &mut egui::Ui) {
+        let duration = self.pipeline.as_ref().unwrap().duration().as_secs_f32();
+        let middle = self.wave_view_start + duration / self.wave_zoom / 2.0;
 
-        self.max_sample = samples[0];
-        for &sample in &samples {
-            if sample > self.max_sample {
-                self.max_sample = sample;
+        ui.horizontal(|ui| {
+            if ui.button("➖").on_hover_text(tr(Key::ZoomOut)).clicked() {
+                self.zoom_wave(1.0 / WAVE_ZOOM_STEP, middle, 0.5, duration);
             }
+            if ui.button("➕").on_hover_text(tr(Key::ZoomIn)).clicked() {
+                self.zoom_wave(WAVE_ZOOM_STEP, middle, 0.5, duration);
+            }
+            if ui
+                .add_enabled(self.wave_zoom > 1.0, egui::Button::new(tr(Key::ZoomToFit)))
+                .clicked()
+            {
+                self.wave_zoom = 1.0;
+                self.wave_view_start = 0.0;
+            }
+            ui.label(tr_args(Key::ZoomHint, &[&format!("{:.1}", self.wave_zoom)]));
+
+            ui.separator();
+            ui.selectable_value(&mut self.show_spectrogram, false, tr(Key::WaveformView));
+            ui.selectable_value(&mut self.show_spectrogram, true, tr(Key::SpectrogramView))
+                .on_hover_text(tr(Key::SpectrogramHint));
+        });
+    }
+
+    /// Zooms the sound wave with mouse wheel around the pointer and scrolls it horizontally, e.g.
+    /// with Shift and mouse wheel.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` with the sound wave.
+    /// * `response` - response of the sound wave area.
+    /// * `frame_rect` - rectangle of the sound wave on the screen.
+    /// * `duration` - total duration of the audio track in seconds.
+    fn zoom_wave_with_pointer(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        frame_rect: egui::Rect,
+        duration: f32,
+    ) {
+        let Some(pointer) = response.hover_pos() else {
+            return;
+        };
+        let (scroll, zoom_delta) = ui.input(|i| (i.smooth_scroll_delta, i.zoom_delta()));
+        let visible_duration = duration / self.wave_zoom;
+
+        if scroll.x != 0.0 {
+            self.wave_view_start -= scroll.x / frame_rect.width() * visible_duration;
+            self.wave_view_start = self.wave_view_start.clamp(0.0, duration - visible_duration);
         }
 
-        self.samples = samples;
+        // Pinch and Ctrl+wheel zoom as well
+        let factor = zoom_delta * (scroll.y / 200.0).exp();
+        if factor != 1.0 {
+            let fraction = (pointer.x - frame_rect.left()) / frame_rect.width();
+            let anchor = self.wave_view_start + fraction * visible_duration;
+            self.zoom_wave(factor, anchor, fraction, duration);
+        }
     }
 
-    /// Controls the behavior of opening file UI button.
+    /// Zooms the sound wave, keeping the anchor position at the same place on the screen.
     ///
     /// # Parameters
     ///
-    /// * `ui` - `egui::UI` for placing the button on.
-    fn open_file_button(&mut self, ui: &mut egui::Ui) {
-        if ui.button("Открыть файл...").clicked() {
-            if let Some(file) = rfd::FileDialog::new()
-                .add_filter("MP3 файл", &["mp3"])
-                .pick_file()
-            {
-                // Stop playing current audio if a new file is chosen
-                self.audio_thread
-                    .as_ref()
-                    .unwrap()
-                    .send(AudioControlCommand::Stop)
-                    .unwrap();
+    /// * `factor` - factor to multiply the zoom by, greater than `1.0` to zoom in.
+    /// * `anchor` - position in seconds, that stays in place.
+    /// * `fraction` - place of the anchor on the sound wave, from `0.0` on the left to `1.0` on
+    ///   the right.
+    /// * `duration` - total duration of the audio track in seconds.
+    fn zoom_wave(&mut self, factor: f32, anchor: f32, fraction: f32, duration: f32) {
+        let max_zoom = (duration / MIN_VISIBLE_WAVE_DURATION).max(1.0);
+        self.wave_zoom = (self.wave_zoom * factor).clamp(1.0, max_zoom);
+        let visible_duration = duration / self.wave_zoom;
+        self.wave_view_start =
+            (anchor - fraction * visible_duration).clamp(0.0, duration - visible_duration);
+    }
 
-                self.current_file_name = Some(file);
+    /// Scrolls the zoomed sound wave a page forward or back, if the playing position has left
+    /// the visible part of it, so the position stays in sight.
+    ///
+    /// # Parameters
+    ///
+    /// * `duration` - total duration of the audio track in seconds.
+    fn follow_playback_on_wave(&mut self, duration: f32) {
+        if !matches!(self.playback_status, PlaybackStatus::Playing) || self.scrubbing {
+            return;
+        }
 
-                self.load_audio_source();
-                self.load_samples();
-            }
+        let visible_duration = duration / self.wave_zoom;
+        let position = self.audio_wave_position;
+        if position < self.wave_view_start || position > self.wave_view_start + visible_duration {
+            self.wave_view_start = position.clamp(0.0, duration - visible_duration);
         }
     }
 
-    /// Controls audio playback part of the UI.
+    /// Chooses the region by dragging across the sound wave and highlights the chosen region.
+    ///
+    /// Dragging starts a new region, unless it starts at a bound of the chosen region, which
+    /// moves that bound then.
     ///
     /// # Parameters
     ///
-    /// * `ui` - `egui::UI` for placing audio playback controls on.
-    fn playback_control(&mut self, ui: &mut egui::Ui) {
-        let action;
-        if let PlaybackStatus::Playing = self.playback_status {
-            action = String::from("Пауза");
-        } else {
-            action = String::from("Играть");
+    /// * `ui` - `egui::UI` with the sound wave.
+    /// * `response` - response of the sound wave area.
+    /// * `to_screen` - transformation from the sound wave coordinates to the screen ones.
+    fn select_region_on_wave(
+        &mut self,
+        ui: &mut egui::Ui,
+        response: &egui::Response,
+        to_screen: emath::RectTransform,
+    ) {
+        // Zoomed in sound wave shows a part of the audio track, so the region is chosen within it
+        let visible = to_screen.from().x_range();
+        let to_position = |x: f32| {
+            let seconds = to_screen.inverse().transform_pos(egui::pos2(x, 0.0)).x;
+            std::time::Duration::from_secs_f32(seconds.clamp(visible.min, visible.max))
+        };
+        // Bounds are thin, so let them be grabbed a bit aside as well
+        let near_bound = |x: f32, bound: std::time::Duration| {
+            ((to_screen * egui::pos2(bound.as_secs_f32(), 0.0)).x - x).abs() <= 4.0
+        };
+
+        if response.drag_started() {
+            // Drag is recognized after the pointer moves a bit, so start from the press point
+            if let Some(origin) = ui.input(|i| i.pointer.press_origin()) {
+                self.region_drag_anchor = match (self.region_start, self.region_end) {
+                    (Some(start), Some(end)) if near_bound(origin.x, start) => Some(end),
+                    (Some(start), Some(end)) if near_bound(origin.x, end) => Some(start),
+                    _ => Some(to_position(origin.x)),
+                };
+            }
         }
 
-        ui.horizontal(|ui| {
-            // Trying to center buttons two buttons in the same row at the center
-            // horizontally, when already horizontally center other widgets from top to down
-            // might be a problem for immediate mode UI:
-            // https://github.com/emilk/egui/discussions/2916
-            // Rude fix is to add some constant space before buttons or need to change the
-            // UI layout (better)
-            // let button_width = 40.0;
-            // let total_width = button_width * 2.0;
-            // ui.add_space((ui.available_width() - total_width) / 2.0);
-            // Button in the widget for stopping
-            if ui.button("Стоп").clicked() {
-                // TODO: Handle Result
-                self.audio_thread
-                    .as_ref()
-                    .unwrap()
-                    .send(AudioControlCommand::Stop)
-                    .unwrap();
-                self.playback_status = PlaybackStatus::Stopped;
-                self.audio_wave_position = 0.0;
-                self.skipped_from_beg = std::time::Duration::ZERO;
+        if let Some(anchor) = self.region_drag_anchor {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let position = to_position(pointer.x);
+                self.region_start = Some(anchor.min(position));
+                self.region_end = Some(anchor.max(position));
             }
 
-            // Button in the widget for playing and pausing
-            if ui.button(action).clicked() {
-                match self.playback_status {
-                    PlaybackStatus::Playing => {
-                        // Pause is the same as stop, but we don't clear audio wave position and
-                        // skipped duration immediately.
-                        //
-                        // In context of AudioThread, we don't differ stop and pause, as user can
-                        // change start time, so we have to send a new audio source to the
-                        // AudioThread each time.
-                        // TODO: Handle Result
-                        self.audio_thread
-                            .as_ref()
-                            .unwrap()
-                            .send(AudioControlCommand::Stop)
-                            .unwrap();
-                        self.playback_status = PlaybackStatus::Stopped;
-                    }
-                    PlaybackStatus::Stopped => {
-                        self.skipped_from_beg =
-                            std::time::Duration::from_secs_f32(self.audio_wave_position);
-                        let source = self
-                            .audio_source
-                            .as_ref()
-                            .unwrap()
-                            .clone()
-                            .skip_duration(self.skipped_from_beg);
-                        self.audio_thread
-                            .as_ref()
-                            .unwrap()
-                            .send(AudioControlCommand::Play(source))
-                            .unwrap();
-                        self.playback_status = PlaybackStatus::Playing;
+            if response.drag_stopped() {
+                self.region_drag_anchor = None;
+                // Playing audio source is bounded by the played region, so a new one is needed
+                if self.played_region().is_some() {
+                    if let PlaybackStatus::Playing = self.playback_status {
+                        self.play_from(self.current_position());
                     }
                 }
             }
-        });
+        }
+
+        if let (Some(start), Some(end)) = (self.region_start, self.region_end) {
+            let top_left = to_screen * egui::pos2(start.as_secs_f32(), to_screen.from().min.y);
+            let bottom_right = to_screen * egui::pos2(end.as_secs_f32(), to_screen.from().max.y);
+            ui.painter().rect_filled(
+                egui::Rect::from_two_pos(top_left, bottom_right),
+                0.0,
+                ui.visuals().selection.bg_fill.gamma_multiply(0.3),
+            );
+        }
     }
 
-    // TODO: use epaint as dancing strings demo?
-    // TODO: look at https://github.com/Cannedfood/egui-audio/tree/main
+    /// Paints a faint marker of the last pause position on the sound wave and seeks there, when
+    /// the marker is clicked.
+    ///
+    /// # Parameters
+    ///
+    /// * `ui` - `egui::UI` with the sound wave.
+    /// * `response` - response of the sound wave area.
+    /// * `to_screen` - transformation from the sound wave coordinates to the screen ones.
+    /// * `pause_position` - the last pause position on the sound wave.
+    fn paint_pause_marker(
+        &mut self,
+        ui: &mut egui::Ui,
+        response: &egui::Response,
+        to_screen: emath::RectTransform,
+        pause_position: f32,
+    ) {
+        let top = to_screen * egui::pos2(pause_position, to_screen.from().min.y);
+        let bottom = to_screen * egui::pos2(pause_position, to_screen.from().max.y);
+        // Marker is thin, so let it be clicked a bit aside as well
+        let marker_rect = egui::Rect::from_x_y_ranges(top.x - 4.0..=top.x + 4.0, top.y..=bottom.y);
 
-    fn paint_sound_wave(&self, ui: &mut egui::Ui) {
-        if self.audio_source.is_none() {
-            return;
+        let hovered = response
+            .hover_pos()
+            .is_some_and(|pos| marker_rect.contains(pos));
+        let color = if hovered {
+            ui.visuals().text_color()
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        ui.painter()
+            .line_segment([top, bottom], egui::Stroke::new(2.0, color));
+
+        if hovered {
+            response.clone().on_hover_text(tr(Key::ResumeFromPause));
         }
 
-        Frame::window(ui.style()).show(ui, |ui| {
-            ui.ctx().request_repaint();
+        let clicked = response.clicked()
+            && response
+                .interact_pointer_pos()
+                .is_some_and(|pos| marker_rect.contains(pos));
+        if clicked {
+            self.seek_to(std::time::Duration::from_secs_f32(pause_position));
+        }
+    }
+}
 
-            // Desired size of the frame with sound wave: occupy all available width (x-coordinate)
-            // and use 25% of the width as height (y-coordinate)
-            let desired_size = ui.available_width() * egui::vec2(1.0, 0.25);
-            let (_id, frame_rect) = ui.allocate_space(desired_size);
+/// Controls a single bound of the region, chosen by user for cutting.
+///
+/// The bound can be typed as an exact timestamp or set to the current position, and set bound can
+/// also be edited as a number of seconds within the audio track duration.
+///
+/// # Parameters
+///
+/// * `ui` - `egui::UI` for placing the bound controls on.
+/// * `bound` - the region bound to edit.
+/// * `text` - the timestamp text, typed by user.
+/// * `position` - the current position on the audio track.
+/// * `duration` - total duration of the audio track.
+fn region_bound_edit(
+    ui: &mut egui::Ui,
+    bound: &mut Option<std::time::Duration>,
+    text: &mut String,
+    position: std::time::Duration,
+    duration: std::time::Duration,
+) {
+    let parse = |text: &str| parse_timestamp(text).filter(|&value| value <= duration);
+    let invalid = !text.is_empty() && parse(text).is_none();
+    let mut timestamp_edit = egui::TextEdit::singleline(text)
+        .desired_width(70.0)
+        .hint_text(tr(Key::TimestampHint));
+    if invalid {
+        timestamp_edit = timestamp_edit.text_color(ui.visuals().error_fg_color);
+    }
+    let mut response = ui.add(timestamp_edit);
+    if invalid {
+        response = response.on_hover_text(tr_args(
+            Key::InvalidTimestamp,
+            &[&format_timestamp(duration)],
+        ));
+    }
+    if response.changed() {
+        if let Some(value) = parse(text) {
+            *bound = Some(value);
+        }
+    }
 
-            // Linear transformation from the rectangle with audio samples bars to frame rectangle
-            let to_screen = emath::RectTransform::from_to(
-                egui::Rect::from_x_y_ranges(0.0..=self.samples.len() as f32, 0.0..=self.max_sample),
-                frame_rect,
-            );
+    if ui
+        .button("⌖")
+        .on_hover_text(tr(Key::SetToPosition))
+        .clicked()
+    {
+        *bound = Some(position);
+    }
 
-            let mut sample_bars = vec![];
+    // Until user edits the text, it follows the bound, changed by other controls
+    if !response.has_focus() {
+        *text = bound.map(format_timestamp).unwrap_or_default();
+    }
+}
 
-            for (second, &sample) in self.samples.iter().enumerate() {
-                let mut points = vec![];
+/// Inserts the region into the list of regions, keeping it sorted by region beginnings and
+/// merging the region with the ones, it overlaps.
+///
+/// # Parameters
+///
+/// * `regions` - the sorted list of regions without overlaps.
+/// * `region` - beginning and end of the region to insert.
+fn insert_region(
+    regions: &mut Vec<(std::time::Duration, std::time::Duration)>,
+    region: (std::time::Duration, std::time::Duration),
+) {
+    regions.push(region);
+    regions.sort();
 
-                // Egui uses a coordinate system, where the left-top corner of the screen is
-                // (0.0, 0.0), with X increasing to the right and Y increasing downwards. So we have
-                // to use maximum sample value (Y) as the bottom of the samples bars, otherwise bars
-                // will be inverted
-                points.push(to_screen * egui::pos2(second as f32, self.max_sample));
-                points.push(to_screen * egui::pos2(second as f32, self.max_sample - sample));
+    let mut merged: Vec<(std::time::Duration, std::time::Duration)> = Vec::new();
+    for &(start, end) in regions.iter() {
+        match merged.last_mut() {
+            Some((_, last_end)) if start < *last_end => *last_end = (*last_end).max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    *regions = merged;
+}
 
-                let thickness = 2.0;
-                let path_stroke;
-                if (second as f32) < self.audio_wave_position {
-                    // The current second is less than elapsed second, so it is green as "completed"
-                    path_stroke = PathStroke::new(thickness, egui::Color32::from_rgb(87, 168, 50));
-                } else {
-                    path_stroke = PathStroke::new(thickness, egui::Color32::from_rgb(168, 64, 50))
+/// Controls the numeric input of a fade or silence duration of the exported regions.
+///
+/// # Parameters
+///
+/// * `ui` - `egui::UI` for placing the input on.
+/// * `enabled` - whether the fade or silence is on, so its duration can be changed.
+/// * `duration` - the duration to show and change.
+fn fade_duration_edit(ui: &mut egui::Ui, enabled: bool, duration: &mut std::time::Duration) {
+    let mut duration_ms = duration.as_millis() as u64;
+    if ui
+        .add_enabled(
+            enabled,
+            egui::DragValue::new(&mut duration_ms)
+                .range(1..=5000)
+                .suffix(tr(Key::MillisecondsSuffix)),
+        )
+        .changed()
+    {
+        *duration = std::time::Duration::from_millis(duration_ms);
+    }
+}
+
+/// Controls the choice of the format of the exported regions, including the MP3 bitrate, and
+/// their channels.
+///
+/// # Parameters
+///
+/// * `ui` - `egui::UI` for placing the choice on.
+/// * `format` - the export format to show and change.
+/// * `channel_mode` - the channels of the exported regions to show and change.
+fn export_format_edit(
+    ui: &mut egui::Ui,
+    format: &mut ExportFormat,
+    channel_mode: &mut ChannelMode,
+) {
+    let formats = [
+        ExportFormat::Wav,
+        ExportFormat::Mp3(Mp3Bitrate::Kbps128),
+        ExportFormat::Mp3(Mp3Bitrate::Kbps192),
+        ExportFormat::Mp3(Mp3Bitrate::Kbps320),
+    ];
+    let format_name = |format: ExportFormat| match format {
+        ExportFormat::Wav => String::from("WAV"),
+        ExportFormat::Mp3(bitrate) => tr_args(Key::Mp3Format, &[&bitrate.kbps()]),
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(tr(Key::Format));
+        egui::ComboBox::from_id_salt("export_format")
+            .selected_text(format_name(*format))
+            .show_ui(ui, |ui| {
+                for choice in formats {
+                    ui.selectable_value(format, choice, format_name(choice));
                 }
+            });
 
-                sample_bars.push(epaint::Shape::line(points, path_stroke));
-            }
+        let mode_name = |mode: ChannelMode| match mode {
+            ChannelMode::Keep => tr(Key::KeepChannels),
+            ChannelMode::Mono => tr(Key::Mono),
+        };
+        ui.label(tr(Key::ExportChannels));
+        egui::ComboBox::from_id_salt("channel_mode")
+            .selected_text(mode_name(*channel_mode))
+            .show_ui(ui, |ui| {
+                for choice in [ChannelMode::Keep, ChannelMode::Mono] {
+                    ui.selectable_value(channel_mode, choice, mode_name(choice));
+                }
+            });
+    });
+}
 
-            ui.painter().extend(sample_bars);
-        });
+/// Opens the directory with the file in the file manager of the OS, selecting the file, where it
+/// is supported.
+///
+/// # Parameters
+///
+/// * `file` - path to the file to show.
+///
+/// # Errors
+///
+/// Returns an error message if the file doesn't exist anymore, e.g. it has been moved or removed
+/// since it was opened, or the file manager can't be started.
+fn reveal_in_file_manager(file: &std::path::Path) -> Result<(), String> {
+    if !file.is_file() {
+        return Err(tr_args(Key::FileNotFound, &[&file.to_string_lossy()]));
+    }
+
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("explorer");
+        // Explorer takes the option and the path as a single argument
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(file);
+        command.arg(select);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("open");
+        command.arg("-R").arg(file);
+        command
+    } else {
+        // There is no common way to select a file on other systems, so open its directory only
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(file.parent().unwrap_or(file));
+        command
+    };
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|error| tr_args(Key::RevealFailed, &[&error]))
+}
+
+/// Formats the duration as minutes and seconds with two digits minimum, e.g. `01:05`.
+///
+/// # Parameters
+///
+/// * `duration` - the duration to format.
+fn format_duration(duration: std::time::Duration) -> String {
+    format!(
+        "{:02}:{:02}",
+        duration.as_secs() / 60,
+        duration.as_secs() % 60
+    )
+}
+
+/// Formats the duration as minutes, seconds and milliseconds, e.g. `01:05.250`.
+///
+/// # Parameters
+///
+/// * `duration` - the duration to format.
+fn format_timestamp(duration: std::time::Duration) -> String {
+    format!(
+        "{}.{:03}",
+        format_duration(duration),
+        duration.subsec_millis()
+    )
+}
+
+/// Parses the timestamp in `mm:ss.mmm` format, in which minutes and milliseconds may be omitted.
+///
+/// Returns `None`, if the text is not a valid timestamp.
+///
+/// # Parameters
+///
+/// * `text` - the text to parse.
+fn parse_timestamp(text: &str) -> Option<std::time::Duration> {
+    let text = text.trim();
+    let (minutes, seconds) = match text.split_once(':') {
+        Some((minutes, seconds)) => (Some(minutes), seconds),
+        None => (None, text),
+    };
+    let (seconds, millis) = match seconds.split_once('.') {
+        Some((seconds, millis)) => (seconds, Some(millis)),
+        None => (seconds, None),
+    };
+
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(seconds)
+        || !minutes.is_none_or(is_number)
+        || !millis.is_none_or(|millis| is_number(millis) && millis.len() <= 3)
+    {
+        return None;
+    }
+
+    let seconds: u64 = seconds.parse().ok()?;
+    let minutes: u64 = match minutes {
+        // Seconds overflowing into minutes are allowed only when minutes are omitted
+        Some(_) if seconds >= 60 => return None,
+        Some(minutes) => minutes.parse().ok()?,
+        None => 0,
+    };
+    // Fraction of a second, e.g. `.5` is 500 ms
+    let millis = millis.map_or(0, |millis| {
+        millis.parse::<u64>().unwrap() * 10u64.pow(3 - millis.len() as u32)
+    });
+
+    Some(std::time::Duration::from_millis(
+        minutes
+            .checked_mul(60)?
+            .checked_add(seconds)?
+            .checked_mul(1000)?
+            .checked_add(millis)?,
+    ))
+}
+
+/// Shortens the text to the given number of characters by replacing its middle with an ellipsis.
+///
+/// # Parameters
+///
+/// * `text` - the text to shorten.
+/// * `max_chars` - maximum number of characters in the result.
+fn elide_middle(text: &str, max_chars: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= max_chars {
+        return String::from(text);
+    }
+
+    // Keep a bit more of the end, as it contains the file name
+    let tail = max_chars / 2;
+    let head = max_chars - tail - 1;
+    let mut elided: String = chars[..head].iter().collect();
+    elided.push('…');
+    elided.extend(&chars[chars.len() - tail..]);
+    elided
+}
+
+/// Returns a short badge text with an icon describing channel layout of an audio track.
+///
+/// # Parameters
+///
+/// * `channels` - number of channels of the audio track. Zero means that no track is loaded.
+fn channels_badge(channels: u16) -> Option<String> {
+    match channels {
+        0 => None,
+        1 => Some(format!("🔈 {}", tr(Key::Mono))),
+        2 => Some(format!("🎧 {}", tr(Key::Stereo))),
+        6 => Some(String::from("🔊 5.1")),
+        n => Some(format!("🔊 {}", tr_args(Key::Channels, &[&n]))),
     }
 }
 
@@ -236,13 +2750,69 @@ impl Default for AudioCutterApp {
     fn default() -> Self {
         Self {
             playback_status: PlaybackStatus::Stopped,
+            pending_plays: 0,
             audio_thread: None,
             current_file_name: None,
-            audio_source: None,
-            samples: Vec::new(),
-            max_sample: 0.0,
+            tabs: Vec::new(),
+            current_tab: 0,
+            last_open_directory: None,
+            recent_files: Vec::new(),
+            file_states: Vec::new(),
+            pipeline: None,
+            load_error: None,
+            reveal_error: None,
+            waveform: Waveform::default(),
+            spectrogram: None,
+            spectrogram_receiver: None,
+            spectrogram_texture: None,
             skipped_from_beg: std::time::Duration::ZERO,
             audio_wave_position: 0.0,
+            scrubbing: false,
+            wave_zoom: 1.0,
+            wave_view_start: 0.0,
+            last_scrub_seek: None,
+            region_drag_anchor: None,
+            region_start: None,
+            region_end: None,
+            region_start_text: String::new(),
+            region_end_text: String::new(),
+            fade_in: false,
+            fade_in_duration: DEFAULT_FADE_DURATION,
+            fade_out: false,
+            fade_out_duration: DEFAULT_FADE_DURATION,
+            normalize: false,
+            gain_db: 0.0,
+            limit: false,
+            export_format: ExportFormat::Wav,
+            channel_mode: ChannelMode::Keep,
+            crossfade: false,
+            crossfade_duration: DEFAULT_CROSSFADE_DURATION,
+            gap: false,
+            gap_duration: DEFAULT_GAP_DURATION,
+            silence_threshold_db: DEFAULT_SILENCE_THRESHOLD_DB,
+            min_silence_duration: DEFAULT_MIN_SILENCE_DURATION,
+            regions: Vec::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            recorded_region_state: RegionState::default(),
+            export_result: None,
+            export_task: None,
+            rewound: true,
+            last_pause_position: None,
+            volume: 1.0,
+            muted: false,
+            speed: 1.0,
+            loop_mode: LoopMode::Off,
+            preview_processed: false,
+            show_full_path: false,
+            show_spectrogram: false,
+            show_sink_status: false,
+            load_mode: pipeline::LoadMode::Auto,
+            ui_scale: 1.0,
+            update_interval: audio_thread::DEFAULT_UPDATE_INTERVAL,
+            hidden_update_interval: audio_thread::DEFAULT_HIDDEN_UPDATE_INTERVAL,
+            busy_task: None,
+            busy_task_shown: false,
         }
     }
 }
@@ -251,10 +2821,28 @@ impl eframe::App for AudioCutterApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // No audio thread launched yet, start it:
         if self.audio_thread.is_none() {
-            println!("[Audio Cutter App] Spawning audio thread ...");
+            log::debug!("Spawning audio thread ...");
             self.audio_thread = Some(audio_thread::AudioThread::spawn(ctx));
         }
 
+        // Let the audio thread save power, while there is nothing to show
+        let minimized = ctx.input(|i| i.viewport().minimized.unwrap_or(false));
+        self.audio_thread
+            .as_ref()
+            .unwrap()
+            .set_window_visible(!minimized);
+
+        // Zoom factor is changed with keyboard shortcuts as well, not only in the settings
+        self.ui_scale = ctx.zoom_factor();
+
+        self.run_busy_task(ctx);
+        self.handle_dropped_files(ctx);
+
+        // Bottom panel has to be added before the central one, so the latter takes the rest space
+        if self.show_sink_status {
+            self.sink_status_bar(ctx);
+        }
+
         // TODO: Do we need egui::Windows for window resizing? It is not native OS window,
         // but a egui windows that is placed inside native
         // TODO: Place each widget handling in a separate method
@@ -266,24 +2854,36 @@ impl eframe::App for AudioCutterApp {
                 ui.heading("Audio Cutter");
 
                 self.open_file_button(ui);
+                self.recent_files_menu(ui);
+                if !self.tabs.is_empty() {
+                    self.tab_bar(ui);
+                }
 
-                if let Some(picked_file) = &self.current_file_name {
-                    ui.horizontal(|ui| {
-                        ui.label("Открытый файл:");
-                        ui.label(picked_file.file_name().unwrap().to_str().unwrap());
-                    });
+                if let Some(error) = &self.load_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                if let Some(error) = self.audio_thread.as_ref().unwrap().error() {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
 
-                    match self.playback_status {
-                        PlaybackStatus::Playing => {
-                            self.audio_wave_position = self.skipped_from_beg.as_secs_f32()
-                                + self
+                if self.current_file_name.is_some() {
+                    self.file_info(ui);
+                    self.file_details(ui);
+
+                    self.handle_playback_status();
+                    self.handle_shortcuts(ctx);
+
+                    // Keep the position, that user is choosing with the slider, until it is
+                    // released
+                    if matches!(self.playback_status, PlaybackStatus::Playing) && !self.scrubbing {
+                        self.audio_wave_position = self.skipped_from_beg.as_secs_f32()
+                            + self
                                 .audio_thread
                                 .as_ref()
                                 .unwrap()
                                 .time_elapsed()
                                 .as_secs_f32();
-                        }
-                        _ => {}
                     }
 
                     self.playback_control(ui);
@@ -291,49 +2891,200 @@ impl eframe::App for AudioCutterApp {
                     self.paint_sound_wave(ui);
 
                     ui.spacing_mut().slider_width = ui.available_width();
-                    if ui
-                        .add(
-                            egui::Slider::new(
-                                &mut self.audio_wave_position,
-                                0.0..=self.samples.len() as f32,
-                            )
-                                .show_value(false),
+                    let slider = ui.add(
+                        egui::Slider::new(
+                            &mut self.audio_wave_position,
+                            0.0..=self.pipeline.as_ref().unwrap().duration().as_secs_f32(),
                         )
-                        .changed()
-                    {
+                        .show_value(false),
+                    );
+                    self.scrubbing = slider.dragged();
+                    // While dragging, seek at most once per SCRUB_SEEK_INTERVAL, so dragging
+                    // doesn't flood the audio thread with seeks, and always seek on release. Clicks
+                    // and keys change the position without dragging
+                    let scrub_seek_due = self.scrubbing
+                        && self.last_scrub_seek.is_none_or(|(time, position)| {
+                            time.elapsed() >= SCRUB_SEEK_INTERVAL
+                                && position != self.audio_wave_position
+                        });
+                    if slider.drag_stopped() || (slider.changed() && !slider.dragged()) {
+                        self.last_scrub_seek = None;
                         ctx.request_repaint();
-                        // If audio wave position is changed with slider, start playing from the new
-                        // position, if we are playing currently
-                        // TODO: Extract sending Play to separate method
-                        if let PlaybackStatus::Playing = self.playback_status {
-                            self.skipped_from_beg =
-                                std::time::Duration::from_secs_f32(self.audio_wave_position);
-                            let source = self
-                                .audio_source
-                                .as_ref()
-                                .unwrap()
-                                .clone()
-                                .skip_duration(self.skipped_from_beg);
-                            self.audio_thread
-                                .as_ref()
-                                .unwrap()
-                                .send(AudioControlCommand::Play(source))
-                                .unwrap();
-                            self.playback_status = PlaybackStatus::Playing;
-                        }
+                        // If audio wave position is changed with slider, continue playing from the
+                        // new position, if we are playing currently
+                        self.seek_to(std::time::Duration::from_secs_f32(self.audio_wave_position));
+                    } else if scrub_seek_due {
+                        self.last_scrub_seek =
+                            Some((std::time::Instant::now(), self.audio_wave_position));
+                        self.seek_to(std::time::Duration::from_secs_f32(self.audio_wave_position));
                     }
 
-                    // Print elapsed time as minutes and seconds with two digits minimum (00:00)
+                    // Print elapsed time and total duration of the track
                     let elapsed_duration =
                         std::time::Duration::from_secs_f32(self.audio_wave_position);
-                    let elapsed_duration = format!(
-                        "{:02}:{:02}",
-                        elapsed_duration.as_secs() / 60,
-                        elapsed_duration.as_secs() % 60
-                    );
-                    ui.label(elapsed_duration);
+                    let total_duration = self.pipeline.as_ref().unwrap().duration();
+                    ui.label(format!(
+                        "{} / {}",
+                        format_duration(elapsed_duration),
+                        format_duration(total_duration)
+                    ));
+
+                    self.copy_position_buttons(ui);
+
+                    self.region_control(ui);
+                    self.record_region_change(ctx);
                 }
+
+                self.settings(ui);
             });
         });
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // Tabs are remembered first, so the current audio track stays the latest one
+        let tab_states: Vec<_> = self
+            .tabs
+            .iter()
+            .flatten()
+            .filter_map(|tab| {
+                Some(FileState {
+                    file: tab.file.clone(),
+                    modified: tab.pipeline.modified()?,
+                    position: tab.position,
+                    region_state: tab.region_state.clone(),
+                })
+            })
+            .collect();
+        for state in tab_states {
+            self.remember_file_state(state);
+        }
+        self.remember_current_file_state();
+
+        eframe::set_value(storage, SHOW_FULL_PATH_KEY, &self.show_full_path);
+        eframe::set_value(storage, LAST_OPEN_DIRECTORY_KEY, &self.last_open_directory);
+        eframe::set_value(storage, RECENT_FILES_KEY, &self.recent_files);
+        eframe::set_value(storage, FILE_STATES_KEY, &self.file_states);
+        eframe::set_value(storage, LOOP_MODE_KEY, &self.loop_mode);
+        eframe::set_value(storage, SHOW_SPECTROGRAM_KEY, &self.show_spectrogram);
+        eframe::set_value(storage, LOAD_MODE_KEY, &self.load_mode);
+        eframe::set_value(storage, UI_SCALE_KEY, &self.ui_scale);
+        eframe::set_value(storage, LANGUAGE_KEY, &i18n::lang().code());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the application, playing the test audio track with the given loop mode, along
+    /// with the sender of playback statuses and the receiver of commands of its audio thread.
+    ///
+    /// # Parameters
+    ///
+    /// * `loop_mode` - what playback does at the end of the audio track.
+    fn playing_app(
+        loop_mode: LoopMode,
+    ) -> (
+        AudioCutterApp,
+        std::sync::mpsc::Sender<PlaybackStatus>,
+        std::sync::mpsc::Receiver<AudioControlCommand>,
+    ) {
+        let (audio_thread, statuses, commands) = audio_thread::AudioThread::detached();
+        let mut app = AudioCutterApp {
+            audio_thread: Some(audio_thread),
+            loop_mode,
+            ..AudioCutterApp::default()
+        };
+        app.pipeline = Some(AudioPipeline::load(std::path::Path::new(crate::TEST_TRACK)).unwrap());
+        app.playback_status = PlaybackStatus::Playing;
+        app.audio_wave_position = 1.5;
+        (app, statuses, commands)
+    }
+
+    #[test]
+    fn finished_track_restarts_with_repeat_track() {
+        let (mut app, statuses, commands) = playing_app(LoopMode::RepeatTrack);
+
+        statuses.send(PlaybackStatus::Stopped).unwrap();
+        app.handle_playback_status();
+
+        assert!(app.playback_status == PlaybackStatus::Playing);
+        assert_eq!(app.skipped_from_beg, std::time::Duration::ZERO);
+        assert!(commands
+            .try_iter()
+            .any(|command| matches!(command, AudioControlCommand::Play(_))));
+    }
+
+    #[test]
+    fn finished_track_stops_without_repeat_track() {
+        let (mut app, statuses, commands) = playing_app(LoopMode::Off);
+
+        statuses.send(PlaybackStatus::Stopped).unwrap();
+        app.handle_playback_status();
+
+        assert!(app.playback_status == PlaybackStatus::Stopped);
+        assert_eq!(app.audio_wave_position, 0.0);
+        assert!(app.rewound);
+        assert!(commands.try_recv().is_err());
+    }
+
+    #[test]
+    fn failed_load_keeps_current_track() {
+        let (audio_thread, _statuses, _commands) = audio_thread::AudioThread::detached();
+        let mut app = AudioCutterApp {
+            audio_thread: Some(audio_thread),
+            ..AudioCutterApp::default()
+        };
+        let track = std::path::PathBuf::from(crate::TEST_TRACK);
+        app.load_file(track.clone());
+        assert!(app.load_error.is_none());
+
+        app.load_file(std::path::PathBuf::from(crate::NOT_AUDIO_FILE));
+
+        assert!(app.load_error.is_some());
+        assert_eq!(app.current_file_name, Some(track));
+        assert_eq!(
+            app.pipeline.as_ref().unwrap().duration(),
+            std::time::Duration::from_millis(1500)
+        );
+        assert_eq!(app.tabs.len(), 1);
+    }
+
+    #[test]
+    fn parse_timestamp_accepts_valid_timestamps() {
+        let cases = [
+            ("5", 5_000),
+            ("5.5", 5_500),
+            ("01:05.250", 65_250),
+            ("90", 90_000),
+            (" 1:02.03 ", 62_030),
+        ];
+
+        for (text, millis) in cases {
+            assert_eq!(
+                parse_timestamp(text),
+                Some(std::time::Duration::from_millis(millis)),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_timestamp_rejects_invalid_timestamps() {
+        let overflow = format!("{}:00", u64::MAX / 60 + 1);
+        let cases = ["1:60", "1:2.3456", ":5", "5.", "1,5", "", "-1", &overflow];
+
+        for text in cases {
+            assert_eq!(parse_timestamp(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn parse_timestamp_reads_formatted_timestamp() {
+        for millis in [0, 999, 59_999, 65_250, 6_000_001] {
+            let duration = std::time::Duration::from_millis(millis);
+
+            assert_eq!(parse_timestamp(&format_timestamp(duration)), Some(duration));
+        }
+    }
 }
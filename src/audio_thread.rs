@@ -1,19 +1,99 @@
+use crate::i18n::{tr, tr_args, Key};
 use eframe::egui;
-use std::sync::mpsc::{SendError, TryRecvError};
+use rodio::Source;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// Default interval between elapsed time updates of the playing audio, while the application
+/// window is shown.
+///
+/// UI is repainted after each update to show the new elapsed time. The shorter the interval, the
+/// smoother the position moves, but the more CPU time is spent on repainting, draining battery
+/// faster.
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default interval between elapsed time updates of the playing audio, while the application
+/// window is hidden (minimized).
+///
+/// Nothing is shown while the window is hidden, so the UI is not repainted then, and the elapsed
+/// time is updated rarer to save power. The longer the interval, the less CPU wake-ups are made,
+/// but the more outdated the elapsed time may be at the moment the window is shown again.
+pub const DEFAULT_HIDDEN_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of played samples, over which the peak level of the playing audio is measured.
+///
+/// It is about 20 ms of 48 kHz stereo audio, so the level meter follows the sound closely, while
+/// the level is published rarely enough to not slow the playback down.
+const PEAK_LEVEL_SAMPLES: usize = 2048;
+
+/// Error message of sending a command to the audio playback thread, that is shut down.
+const THREAD_SHUT_DOWN_ERROR: &str = "audio thread is shut down";
+
 /// Commands to control a thread, that performs audio playback.
 pub enum AudioControlCommand {
     /// Play command to start new playback with a new audio source.
     ///
     /// We don't use command to continue playing the current audio source, as user may change
     /// audio source start point, so we always need a new audio source.
-    Play(rodio::source::SkipDuration<crate::AudioSourceBuf>),
+    Play(crate::PlayedSource),
     /// Stop command.
     ///
     /// As explained about continue, that is not needed, we don't need pause command as well.
     Stop,
+    /// Seek command to jump to the given position of the playing audio source.
+    ///
+    /// The position is counted from the beginning of the audio source, sent with the last
+    /// [AudioControlCommand::Play], the same way the elapsed time is. Ignored, if nothing is
+    /// playing.
+    Seek(Duration),
+    /// Sets playback volume, where `1.0` is the original one. While muted, the volume is
+    /// remembered and applied on unmute.
+    SetVolume(f32),
+    /// Sets whether playback is muted, keeping the volume for unmuting.
+    SetMuted(bool),
+    /// Sets playback speed factor, where `1.0` is the original speed. Pitch changes along with
+    /// the speed. The playing audio source is restarted from its current position, so the elapsed
+    /// time stays exact.
+    SetSpeed(f32),
+    /// Sets the interval between elapsed time updates, used while the application window is
+    /// shown. See [DEFAULT_UPDATE_INTERVAL].
+    SetUpdateInterval(Duration),
+    /// Sets the interval between elapsed time updates, used while the application window is
+    /// hidden. See [DEFAULT_HIDDEN_UPDATE_INTERVAL].
+    SetHiddenUpdateInterval(Duration),
+    /// Sets whether to restart the audio source, sent with the last [AudioControlCommand::Play],
+    /// from its beginning once it is played to its end, instead of finishing the track.
+    SetLoop(bool),
+    /// Asks for the exact elapsed time of the playing audio, that is sent back with the given
+    /// sender, once all the commands, sent before, are handled. See
+    /// [AudioThread::query_time_elapsed].
+    QueryTimeElapsed(Sender<Duration>),
+}
+
+/// Audio playback status, reported by the audio playback thread on each change.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PlaybackStatus {
+    Playing,
+    Stopped,
+    /// Playback is stopped, as the audio output device has been lost, e.g. unplugged. The elapsed
+    /// time is kept, so playback can be resumed from there.
+    Interrupted,
+}
+
+/// Snapshot of [rodio::Sink] state, published by the audio playback thread for diagnostics.
+#[derive(Clone, Default)]
+pub struct SinkStatus {
+    /// Whether the sink has no sources to play.
+    pub empty: bool,
+    /// Whether the sink is paused.
+    pub paused: bool,
+    /// Current playback position, as reported by [rodio::Sink::get_pos].
+    pub position: Duration,
+    /// Number of sources queued in the sink.
+    pub queued: usize,
+    /// Current sink volume.
+    pub volume: f32,
 }
 
 /// Struct that owns and controls a thread, that performs an audio playback process.
@@ -23,6 +103,17 @@ pub struct AudioThread {
     /// Handle is wrapped in [Option] for graceful joining, when [AudioThread] is dropped.
     thread_handle: Option<std::thread::JoinHandle<()>>,
     time_elapsed: Arc<Mutex<Duration>>,
+    /// Peak absolute sample value of the latest played samples, see [PEAK_LEVEL_SAMPLES].
+    peak_level: Arc<Mutex<f32>>,
+    sink_status: Arc<Mutex<SinkStatus>>,
+    /// Receives playback status from the audio thread, whenever it starts or stops playing,
+    /// including the track being played to its end.
+    status_receiver: Receiver<PlaybackStatus>,
+    /// Whether the application window is shown, set by UI.
+    window_visible: Arc<Mutex<bool>>,
+    /// Error message set by the audio thread, when playback is impossible at all or the audio
+    /// output device has been lost.
+    error: Arc<Mutex<Option<String>>>,
     commands_sender: Option<std::sync::mpsc::Sender<AudioControlCommand>>,
 }
 
@@ -39,10 +130,28 @@ impl AudioThread {
     pub fn spawn(ui_ctx: &egui::Context) -> Self {
         let (sender, receiver) = std::sync::mpsc::channel();
         let time_elapsed = Arc::new(Mutex::new(Duration::ZERO));
+        let peak_level = Arc::new(Mutex::new(0.0));
+        let sink_status = Arc::new(Mutex::new(SinkStatus::default()));
+        let (status_sender, status_receiver) = std::sync::mpsc::channel();
+        let window_visible = Arc::new(Mutex::new(true));
+        let error = Arc::new(Mutex::new(None));
 
         let thread_ctx = ThreadContext {
             commands_receiver: receiver,
             time_elapsed: Arc::clone(&time_elapsed),
+            peak_level: Arc::clone(&peak_level),
+            sink_status: Arc::clone(&sink_status),
+            status_sender,
+            window_visible: Arc::clone(&window_visible),
+            error: Arc::clone(&error),
+            update_interval: DEFAULT_UPDATE_INTERVAL,
+            hidden_update_interval: DEFAULT_HIDDEN_UPDATE_INTERVAL,
+            volume: 1.0,
+            muted: false,
+            played_source: None,
+            position_offset: Duration::ZERO,
+            looping: false,
+            queued_sources: 0,
             ui_ctx: ui_ctx.clone(),
         };
 
@@ -53,23 +162,110 @@ impl AudioThread {
         Self {
             thread_handle: Option::from(thread_handle),
             time_elapsed,
+            peak_level,
+            sink_status,
+            status_receiver,
+            window_visible,
+            error,
             commands_sender: Option::from(sender),
         }
     }
 
+    /// Creates a new [AudioThread] object without an audio thread, so UI logic can be driven in
+    /// tests without an audio output device.
+    ///
+    /// Returns it along with the sender of playback statuses, as if reported by the audio thread,
+    /// and the receiver of the commands, sent to the audio thread.
+    #[cfg(test)]
+    pub(crate) fn detached() -> (Self, Sender<PlaybackStatus>, Receiver<AudioControlCommand>) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let (status_sender, status_receiver) = std::sync::mpsc::channel();
+        let audio_thread = Self {
+            thread_handle: None,
+            time_elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+            peak_level: Arc::new(Mutex::new(0.0)),
+            sink_status: Arc::new(Mutex::new(SinkStatus::default())),
+            status_receiver,
+            window_visible: Arc::new(Mutex::new(true)),
+            error: Arc::new(Mutex::new(None)),
+            commands_sender: Some(sender),
+        };
+        (audio_thread, status_sender, receiver)
+    }
+
     /// Sends a command to the audio playback thread.
     ///
     /// # Parameters
     ///
     /// * `command` - the command to send to the audio playback thread.
-    pub fn send(&self, command: AudioControlCommand) -> Result<(), SendError<AudioControlCommand>> {
-        self.commands_sender.as_ref().unwrap().send(command)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if the audio playback thread is shut down, so nobody receives the
+    /// command.
+    pub fn send(&self, command: AudioControlCommand) -> Result<(), String> {
+        // Sender is taken only on drop, and receiver is gone only if the thread has finished
+        self.commands_sender
+            .as_ref()
+            .ok_or_else(|| String::from(THREAD_SHUT_DOWN_ERROR))?
+            .send(command)
+            .map_err(|_| String::from(THREAD_SHUT_DOWN_ERROR))
     }
 
     /// Returns the current duration of audio track elapsed time.
     pub fn time_elapsed(&self) -> Duration {
         *self.time_elapsed.lock().unwrap()
     }
+
+    /// Asks the audio thread for the exact elapsed time of the playing audio and waits for the
+    /// answer.
+    ///
+    /// Unlike [AudioThread::time_elapsed], that may be up to an update interval old, the elapsed
+    /// time is taken right after all the commands, sent before, are handled, e.g. a seek. The
+    /// published elapsed time is updated as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if the audio playback thread is shut down, or can't play audio at
+    /// all, so nobody answers.
+    pub fn query_time_elapsed(&self) -> Result<Duration, String> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.send(AudioControlCommand::QueryTimeElapsed(sender))?;
+        receiver
+            .recv()
+            .map_err(|_| String::from(THREAD_SHUT_DOWN_ERROR))
+    }
+
+    /// Returns the peak level of the latest played samples, where `1.0` is the full scale. The
+    /// playback volume is not applied to it.
+    pub fn peak_level(&self) -> f32 {
+        *self.peak_level.lock().unwrap()
+    }
+
+    /// Returns the latest snapshot of the audio sink state.
+    pub fn sink_status(&self) -> SinkStatus {
+        self.sink_status.lock().unwrap().clone()
+    }
+
+    /// Returns the next playback status, reported by the audio thread, if any.
+    pub fn try_recv_status(&self) -> Option<PlaybackStatus> {
+        self.status_receiver.try_recv().ok()
+    }
+
+    /// Returns the error message, if the audio thread can't play audio, e.g. there is no audio
+    /// output device.
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+
+    /// Tells the audio thread whether the application window is shown.
+    ///
+    /// # Parameters
+    ///
+    /// * `visible` - `false` if the window is hidden (minimized).
+    pub fn set_window_visible(&self, visible: bool) {
+        *self.window_visible.lock().unwrap() = visible;
+    }
 }
 
 impl Drop for AudioThread {
@@ -90,6 +286,29 @@ impl Drop for AudioThread {
 struct ThreadContext {
     commands_receiver: std::sync::mpsc::Receiver<AudioControlCommand>,
     time_elapsed: Arc<Mutex<Duration>>,
+    peak_level: Arc<Mutex<f32>>,
+    sink_status: Arc<Mutex<SinkStatus>>,
+    status_sender: Sender<PlaybackStatus>,
+    window_visible: Arc<Mutex<bool>>,
+    error: Arc<Mutex<Option<String>>>,
+    update_interval: Duration,
+    hidden_update_interval: Duration,
+    /// Playback volume, set by [AudioControlCommand::SetVolume], that the sink has, unless muted.
+    volume: f32,
+    /// Whether playback is muted. See [AudioControlCommand::SetMuted].
+    muted: bool,
+    /// Copy of the audio source, sent with the last [AudioControlCommand::Play], that is not
+    /// played yet. Is used for seeking, when the audio source itself doesn't support it.
+    played_source: Option<crate::PlayedSource>,
+    /// Position of the played audio source, from which the sink has started playing. Sink
+    /// reports its position relative to this one.
+    position_offset: Duration,
+    /// Whether the played audio source is restarted once it ends. See
+    /// [AudioControlCommand::SetLoop].
+    looping: bool,
+    /// Number of audio sources, queued in the sink at the last check. Is used to notice, that
+    /// the sink has moved to the next queued copy of the played audio source.
+    queued_sources: usize,
     ui_ctx: egui::Context,
 }
 
@@ -98,10 +317,23 @@ struct ThreadContext {
 /// # Parameters
 ///
 /// * `thread_ctx` - playback context data, controlled by the audio playback thread.
-fn playback_audio(thread_ctx: ThreadContext) {
+fn playback_audio(mut thread_ctx: ThreadContext) {
+    // Audio stream reports its errors from the rodio thread, so they are passed here over a channel
+    let (stream_errors_sender, stream_errors) = std::sync::mpsc::channel();
     // For default physical audio device, create output stream. Audio stream must exist, or playback
     // will end and attached handle will no longer work
-    let audio_stream = rodio::OutputStreamBuilder::open_default_stream().unwrap();
+    let mut audio_stream = match open_output_stream(&stream_errors_sender) {
+        Ok(audio_stream) => audio_stream,
+        Err(error) => {
+            log::error!("Failed to open audio output: {}", error);
+            *thread_ctx.error.lock().unwrap() = Some(tr_args(Key::OutputOpenFailed, &[&error]));
+            thread_ctx.ui_ctx.request_repaint();
+
+            // Keep receiving commands until disconnected, as UI expects them to be sent
+            while thread_ctx.commands_receiver.recv().is_ok() {}
+            return;
+        }
+    };
 
     // Sink is a handle for easier playback control and represents audio track.
     //
@@ -114,14 +346,61 @@ fn playback_audio(thread_ctx: ThreadContext) {
     // TODO: If we place Sink in main thread we will not able to update elapsed time, when no
     // TODO: actions are performed on the UI, right? As update() will not be called then. So
     // TODO: separate thread is needed anyway
-    let audio_sink = rodio::Sink::connect_new(audio_stream.mixer());
+    let mut audio_sink = rodio::Sink::connect_new(audio_stream.mixer());
+    // Whether the sink had a track to play after the last handled command. Is used to tell the
+    // track ending on its own from the sink being cleared by a command
+    let mut track_loaded = false;
+    // Whether the audio output device has been lost and no new output stream is opened since then
+    let mut device_lost = false;
 
     loop {
+        // All pending errors are taken, so the ones of the replaced output stream are not left
+        let lost = stream_errors.try_iter().fold(false, |lost, error| {
+            log::warn!("Audio output stream error: {}", error);
+            lost || is_device_lost(&error)
+        });
+        if lost {
+            interrupt_playback(&mut thread_ctx, &audio_sink);
+            track_loaded = false;
+            // Default output device is likely another one now, e.g. built-in speakers
+            device_lost = !reopen_output(&stream_errors_sender, &mut audio_stream, &mut audio_sink);
+        }
+
+        if thread_ctx.looping {
+            queue_loop_copy(&mut thread_ctx, &audio_sink);
+        }
+
+        if track_loaded && audio_sink.empty() {
+            track_loaded = false;
+            // The last elapsed time update was made some time before the end, so don't leave it
+            // frozen there, but clear it like on stop
+            *thread_ctx.time_elapsed.lock().unwrap() = Duration::ZERO;
+            *thread_ctx.peak_level.lock().unwrap() = 0.0;
+            report_status(&thread_ctx, PlaybackStatus::Stopped);
+        }
+
         if audio_sink.empty() || audio_sink.is_paused() {
             // If no sound is currently playing we can use blocking wait for new command in
-            // order to save CPU time
+            // order to save CPU time. Publish the sink state before, as it will not be updated
+            // until a new command arrives
+            update_sink_status(&thread_ctx, &audio_sink);
             if let Ok(command) = thread_ctx.commands_receiver.recv() {
-                handle_command(&thread_ctx, command, &audio_sink);
+                if let AudioControlCommand::Play(_) = command {
+                    // The device could be plugged back in, so retry on each user attempt to play
+                    if device_lost {
+                        device_lost = !reopen_output(
+                            &stream_errors_sender,
+                            &mut audio_stream,
+                            &mut audio_sink,
+                        );
+                    }
+                    if !device_lost {
+                        *thread_ctx.error.lock().unwrap() = None;
+                    }
+                }
+                handle_command(&mut thread_ctx, command, &audio_sink);
+                track_loaded = !audio_sink.empty();
+                thread_ctx.queued_sources = audio_sink.len();
                 continue;
             } else {
                 // Disconnected
@@ -131,21 +410,182 @@ fn playback_audio(thread_ctx: ThreadContext) {
 
         // Otherwise sound is playing, and we have to handle new command or update elapsed time
         // without blocking
-        match thread_ctx.commands_receiver.try_recv() {
-            Ok(command) => handle_command(&thread_ctx, command, &audio_sink),
-            Err(error) => {
-                if let TryRecvError::Disconnected = error {
-                    return;
-                }
-
+        let command = match thread_ctx.commands_receiver.try_recv() {
+            Ok(command) => command,
+            Err(TryRecvError::Disconnected) => return,
+            Err(TryRecvError::Empty) => {
                 // No commands yet: update elapsed time of the audio
-                *thread_ctx.time_elapsed.lock().unwrap() = audio_sink.get_pos();
-                // Force UI repainting to show new elapsed time
-                thread_ctx.ui_ctx.request_repaint();
-                std::thread::sleep(Duration::from_millis(100));
+                *thread_ctx.time_elapsed.lock().unwrap() =
+                    thread_ctx.position_offset + track_position(&audio_sink);
+                update_sink_status(&thread_ctx, &audio_sink);
+
+                let interval = if *thread_ctx.window_visible.lock().unwrap() {
+                    // Force UI repainting to show new elapsed time
+                    thread_ctx.ui_ctx.request_repaint();
+                    thread_ctx.update_interval
+                } else {
+                    // Nothing to show, so don't waste power on repainting and waking up often
+                    thread_ctx.hidden_update_interval
+                };
+                // Wait for the next command instead of sleeping, so it is handled at once, e.g.
+                // UI doesn't wait for the answer to a query for the whole interval
+                match thread_ctx.commands_receiver.recv_timeout(interval) {
+                    Ok(command) => command,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
             }
+        };
+        handle_command(&mut thread_ctx, command, &audio_sink);
+        track_loaded = !audio_sink.empty();
+        thread_ctx.queued_sources = audio_sink.len();
+    }
+}
+
+/// Opens an output stream of the default audio output device, that sends its errors to the given
+/// sender.
+///
+/// # Parameters
+///
+/// * `errors_sender` - sender end of the channel for the stream errors.
+fn open_output_stream(
+    errors_sender: &Sender<rodio::cpal::StreamError>,
+) -> Result<rodio::OutputStream, rodio::StreamError> {
+    let errors_sender = errors_sender.clone();
+    rodio::OutputStreamBuilder::from_default_device()?
+        .with_error_callback(move |error| {
+            // Receiver is gone only when the audio thread is finished
+            let _ = errors_sender.send(error);
+        })
+        .open_stream_or_fallback()
+}
+
+/// Replaces the audio output stream and the sink with new ones of the default audio output device.
+///
+/// Returns whether the new output stream has been opened. Otherwise, the old ones are kept.
+///
+/// # Parameters
+///
+/// * `errors_sender` - sender end of the channel for the stream errors.
+/// * `audio_stream` - the audio output stream to replace.
+/// * `audio_sink` - the sink, playing into the audio output stream, to replace.
+fn reopen_output(
+    errors_sender: &Sender<rodio::cpal::StreamError>,
+    audio_stream: &mut rodio::OutputStream,
+    audio_sink: &mut rodio::Sink,
+) -> bool {
+    match open_output_stream(errors_sender) {
+        Ok(stream) => {
+            // New sink starts with the default volume and speed, so keep the chosen ones
+            let sink = rodio::Sink::connect_new(stream.mixer());
+            sink.set_volume(audio_sink.volume());
+            sink.set_speed(audio_sink.speed());
+            *audio_sink = sink;
+            *audio_stream = stream;
+            true
+        }
+        Err(error) => {
+            log::warn!("Failed to reopen audio output: {}", error);
+            false
+        }
+    }
+}
+
+/// Returns whether the audio output stream error means, that the audio output device is gone.
+///
+/// # Parameters
+///
+/// * `error` - the error, reported by the audio output stream.
+fn is_device_lost(error: &rodio::cpal::StreamError) -> bool {
+    matches!(error, rodio::cpal::StreamError::DeviceNotAvailable)
+}
+
+/// Stops playback, as the audio output device is lost, keeping the elapsed time for resuming, and
+/// lets user know about it.
+///
+/// # Parameters
+///
+/// * `thread_ctx` - playback context data, controlled by the audio playback thread.
+/// * `audio_sink` - [rodio::Sink] that actually performs audio playback.
+fn interrupt_playback(thread_ctx: &mut ThreadContext, audio_sink: &rodio::Sink) {
+    if !audio_sink.empty() {
+        *thread_ctx.time_elapsed.lock().unwrap() =
+            thread_ctx.position_offset + track_position(audio_sink);
+    }
+    audio_sink.clear();
+    thread_ctx.played_source = None;
+    *thread_ctx.peak_level.lock().unwrap() = 0.0;
+    *thread_ctx.error.lock().unwrap() = Some(String::from(tr(Key::DeviceLost)));
+    report_status(thread_ctx, PlaybackStatus::Interrupted);
+}
+
+/// Keeps the next copy of the played audio source queued after the playing one, so it starts
+/// right after the playing one ends, without a gap.
+///
+/// # Parameters
+///
+/// * `thread_ctx` - playback context data, controlled by the audio playback thread.
+/// * `audio_sink` - [rodio::Sink] that actually performs audio playback.
+fn queue_loop_copy(thread_ctx: &mut ThreadContext, audio_sink: &rodio::Sink) {
+    let queued_sources = audio_sink.len();
+    if queued_sources < thread_ctx.queued_sources {
+        // The previous copy has ended, and sink reports position of the next one, that is
+        // played from its beginning
+        thread_ctx.position_offset = Duration::ZERO;
+    }
+
+    if queued_sources == 1 {
+        if let Some(played_source) = &thread_ctx.played_source {
+            audio_sink.append(PeakMeter::new(
+                played_source.clone(),
+                Arc::clone(&thread_ctx.peak_level),
+            ));
         }
     }
+    thread_ctx.queued_sources = audio_sink.len();
+}
+
+/// Returns position of the audio source, played by the sink.
+///
+/// Sink reports its position as a playback time, i.e. with speed applied, so with speed of `2.0`
+/// the audio source is played twice as far, as the sink reports. Sink accumulates the playback
+/// time with the speed in effect at the time, while it is scaled back by the current speed only.
+/// So the audio source position is exact only if the speed has not changed since the audio source
+/// started playing, that is why playback is restarted from the position on each speed change.
+///
+/// # Parameters
+///
+/// * `audio_sink` - [rodio::Sink] that actually performs audio playback.
+fn track_position(audio_sink: &rodio::Sink) -> Duration {
+    audio_sink.get_pos().mul_f32(audio_sink.speed())
+}
+
+/// Reports the playback status to UI and forces UI repainting, so it reacts to the new status.
+///
+/// # Parameters
+///
+/// * `thread_ctx` - playback context data, controlled by the audio playback thread.
+/// * `status` - the new playback status.
+fn report_status(thread_ctx: &ThreadContext, status: PlaybackStatus) {
+    // Receiver is gone only when UI is shut down, so there is nobody to report to anyway
+    let _ = thread_ctx.status_sender.send(status);
+    thread_ctx.ui_ctx.request_repaint();
+}
+
+/// Publishes the current state of the audio sink for diagnostics.
+///
+/// # Parameters
+///
+/// * `thread_ctx` - playback context data, controlled by the audio playback thread.
+/// * `audio_sink` - [rodio::Sink] that actually performs audio playback.
+fn update_sink_status(thread_ctx: &ThreadContext, audio_sink: &rodio::Sink) {
+    *thread_ctx.sink_status.lock().unwrap() = SinkStatus {
+        empty: audio_sink.empty(),
+        paused: audio_sink.is_paused(),
+        position: audio_sink.get_pos(),
+        queued: audio_sink.len(),
+        volume: audio_sink.volume(),
+    };
 }
 
 /// Handles single received audio control command.
@@ -156,7 +596,7 @@ fn playback_audio(thread_ctx: ThreadContext) {
 /// * `command` - the command to handle.
 /// * `audio_sink` - [rodio::Sink] that actually performs audio playback.
 fn handle_command(
-    thread_ctx: &ThreadContext,
+    thread_ctx: &mut ThreadContext,
     command: AudioControlCommand,
     audio_sink: &rodio::Sink,
 ) {
@@ -166,17 +606,227 @@ fn handle_command(
             // Note that stop() should not be used generally, as sink shouldn't be used after
             // stop(): https://github.com/RustAudio/rodio/issues/171
             audio_sink.clear();
+            thread_ctx.played_source = Some(audio_source.clone());
+            thread_ctx.position_offset = Duration::ZERO;
             // The sound starts playing in the separate thread, controlled by the sink, once
             // some data is appended to the sink, if it is not paused
-            audio_sink.append(audio_source);
+            audio_sink.append(PeakMeter::new(
+                audio_source,
+                Arc::clone(&thread_ctx.peak_level),
+            ));
             audio_sink.play();
+            report_status(thread_ctx, PlaybackStatus::Playing);
         }
         AudioControlCommand::Stop => {
             audio_sink.clear();
+            thread_ctx.played_source = None;
             // Also clear elapsed time of the audio
             *thread_ctx.time_elapsed.lock().unwrap() = Duration::ZERO;
-            // Force UI repainting to show new elapsed time
-            thread_ctx.ui_ctx.request_repaint();
+            *thread_ctx.peak_level.lock().unwrap() = 0.0;
+            report_status(thread_ctx, PlaybackStatus::Stopped);
+        }
+        AudioControlCommand::Seek(mut position) => {
+            // Only the latest of the queued seeks matters, so skip the outdated ones instead of
+            // seeking to each of them
+            let mut next_command = None;
+            while let Ok(command) = thread_ctx.commands_receiver.try_recv() {
+                match command {
+                    AudioControlCommand::Seek(next_position) => position = next_position,
+                    command => {
+                        next_command = Some(command);
+                        break;
+                    }
+                }
+            }
+            seek(thread_ctx, position, audio_sink);
+            if let Some(command) = next_command {
+                handle_command(thread_ctx, command, audio_sink);
+            }
+        }
+        AudioControlCommand::SetVolume(volume) => {
+            thread_ctx.volume = volume;
+            apply_volume(thread_ctx, audio_sink);
+        }
+        AudioControlCommand::SetMuted(muted) => {
+            thread_ctx.muted = muted;
+            apply_volume(thread_ctx, audio_sink);
+        }
+        AudioControlCommand::SetSpeed(speed) => {
+            if speed == audio_sink.speed() || audio_sink.empty() {
+                audio_sink.set_speed(speed);
+                return;
+            }
+            // Sink position is scaled by the current speed only, see [track_position], so the
+            // position, played at the former speed, is taken before the change, and playback is
+            // restarted from it, so the sink counts its position from there at the new speed
+            let position = thread_ctx.position_offset + track_position(audio_sink);
+            audio_sink.set_speed(speed);
+            restart_from(thread_ctx, position, audio_sink);
+        }
+        AudioControlCommand::SetUpdateInterval(interval) => thread_ctx.update_interval = interval,
+        AudioControlCommand::SetHiddenUpdateInterval(interval) => {
+            thread_ctx.hidden_update_interval = interval;
+        }
+        AudioControlCommand::SetLoop(looping) => {
+            thread_ctx.looping = looping;
+            if !looping && audio_sink.len() > 1 {
+                // Drop the queued copy, so the track finishes once the playing one ends
+                let position = thread_ctx.position_offset + track_position(audio_sink);
+                restart_from(thread_ctx, position, audio_sink);
+            }
+        }
+        AudioControlCommand::QueryTimeElapsed(answer_sender) => {
+            if !audio_sink.empty() {
+                *thread_ctx.time_elapsed.lock().unwrap() =
+                    thread_ctx.position_offset + track_position(audio_sink);
+            }
+            // Nobody waits for the answer anymore, if the receiver is gone
+            let _ = answer_sender.send(*thread_ctx.time_elapsed.lock().unwrap());
         }
     }
 }
+
+/// Sets the sink volume to the playback volume, or silences the sink, if muted.
+///
+/// # Parameters
+///
+/// * `thread_ctx` - playback context data, controlled by the audio playback thread.
+/// * `audio_sink` - [rodio::Sink] that actually performs audio playback.
+fn apply_volume(thread_ctx: &ThreadContext, audio_sink: &rodio::Sink) {
+    audio_sink.set_volume(if thread_ctx.muted {
+        0.0
+    } else {
+        thread_ctx.volume
+    });
+}
+
+/// Jumps to the given position of the playing audio source.
+///
+/// # Parameters
+///
+/// * `thread_ctx` - playback context data, controlled by the audio playback thread.
+/// * `position` - position, counted from the beginning of the played audio source.
+/// * `audio_sink` - [rodio::Sink] that actually performs audio playback.
+fn seek(thread_ctx: &mut ThreadContext, position: Duration, audio_sink: &rodio::Sink) {
+    // Sink keeps seek request until some source is appended, if there are no sources. Then it
+    // would be applied to the next played audio source, which must start from its beginning
+    if audio_sink.empty() {
+        log::debug!("Nothing is playing, seek is ignored");
+        return;
+    }
+
+    match audio_sink.try_seek(position) {
+        // Sink reports the requested position after seeking, so no offset is needed anymore
+        Ok(()) => thread_ctx.position_offset = Duration::ZERO,
+        Err(rodio::source::SeekError::NotSupported { underlying_source }) => {
+            // Buffered audio sources can't seek, so start playing a copy of the played audio
+            // source from the requested position instead
+            log::debug!(
+                "Seeking is not supported by {}, restarting from the position",
+                underlying_source
+            );
+            restart_from(thread_ctx, position, audio_sink);
+        }
+        Err(error) => {
+            log::warn!("Failed to seek: {}", error);
+            return;
+        }
+    }
+
+    // Show the new position right away, without waiting for the next elapsed time update
+    *thread_ctx.time_elapsed.lock().unwrap() = position;
+    thread_ctx.ui_ctx.request_repaint();
+}
+
+/// Starts playing a copy of the played audio source from the given position instead of
+/// everything queued in the sink.
+///
+/// # Parameters
+///
+/// * `thread_ctx` - playback context data, controlled by the audio playback thread.
+/// * `position` - position, counted from the beginning of the played audio source.
+/// * `audio_sink` - [rodio::Sink] that actually performs audio playback.
+fn restart_from(thread_ctx: &mut ThreadContext, position: Duration, audio_sink: &rodio::Sink) {
+    if let Some(played_source) = &thread_ctx.played_source {
+        audio_sink.clear();
+        audio_sink.append(PeakMeter::new(
+            played_source.starting_at(position),
+            Arc::clone(&thread_ctx.peak_level),
+        ));
+        audio_sink.play();
+        thread_ctx.position_offset = position;
+    }
+}
+
+/// Audio source, that measures the peak level of the samples, played from the inner audio source,
+/// and publishes it once per [PEAK_LEVEL_SAMPLES] samples.
+struct PeakMeter<S> {
+    /// The inner audio source.
+    source: S,
+    /// Published peak level, shared with UI.
+    peak_level: Arc<Mutex<f32>>,
+    /// Peak level of the samples, played since the last publishing.
+    block_peak: f32,
+    /// Number of samples, played since the last publishing.
+    block_samples: usize,
+}
+
+impl<S: Source> PeakMeter<S> {
+    /// Creates a new [PeakMeter] over the audio source.
+    ///
+    /// # Parameters
+    ///
+    /// * `source` - the audio source to measure.
+    /// * `peak_level` - where to publish the peak level to.
+    fn new(source: S, peak_level: Arc<Mutex<f32>>) -> Self {
+        Self {
+            source,
+            peak_level,
+            block_peak: 0.0,
+            block_samples: 0,
+        }
+    }
+}
+
+impl<S: Source> Iterator for PeakMeter<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<rodio::Sample> {
+        let sample = self.source.next()?;
+        self.block_peak = self.block_peak.max(sample.abs());
+        self.block_samples += 1;
+        // Locking on every sample would slow the playback down, so publish once per block
+        if self.block_samples == PEAK_LEVEL_SAMPLES {
+            *self.peak_level.lock().unwrap() = self.block_peak;
+            self.block_peak = 0.0;
+            self.block_samples = 0;
+        }
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.source.size_hint()
+    }
+}
+
+impl<S: Source> Source for PeakMeter<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), rodio::source::SeekError> {
+        self.source.try_seek(position)
+    }
+}
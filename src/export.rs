@@ -0,0 +1,739 @@
+use crate::i18n::{tr, tr_args, Key};
+use std::path::Path;
+
+/// Writes the samples to a new 16-bit PCM WAV file.
+///
+/// Samples outside of the `-1.0..=1.0` range are clipped.
+///
+/// # Parameters
+///
+/// * `path` - path to the file to write. Existing file is overwritten.
+/// * `samples` - interleaved samples of all channels.
+/// * `sample_rate` - number of frames per second.
+/// * `channels` - number of channels.
+pub fn write_wav(
+    path: &Path,
+    samples: impl Iterator<Item = f32>,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<(), hound::Error> {
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for sample in samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16)?;
+    }
+    writer.finalize()
+}
+
+/// Checks, that the exported file is not the source audio file itself.
+///
+/// Audio track is read from the source file lazily, so overwriting it would corrupt both the
+/// source audio track and the exported file.
+///
+/// # Parameters
+///
+/// * `source` - path to the source audio file.
+/// * `destination` - path to the file to export to.
+///
+/// # Errors
+///
+/// Returns an error message if both paths point to the same file.
+pub fn check_destination(source: &Path, destination: &Path) -> Result<(), String> {
+    // Different paths may point to the same file, e.g. through links. Destination usually
+    // doesn't exist yet, so it is compared as is then
+    let source = std::fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf());
+    let destination =
+        std::fs::canonicalize(destination).unwrap_or_else(|_| destination.to_path_buf());
+    if source == destination {
+        return Err(String::from(tr(Key::ExportOverSource)));
+    }
+    Ok(())
+}
+
+/// Channels of exported audio files.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChannelMode {
+    /// The same channels as the source audio track has.
+    Keep,
+    /// A single channel, mixed down from all channels of the source audio track.
+    Mono,
+}
+
+impl ChannelMode {
+    /// Returns number of channels of the exported audio.
+    ///
+    /// # Parameters
+    ///
+    /// * `source_channels` - number of channels of the source audio track.
+    pub fn channels(self, source_channels: u16) -> u16 {
+        match self {
+            ChannelMode::Keep => source_channels,
+            ChannelMode::Mono => 1,
+        }
+    }
+}
+
+/// Iterator over samples, mixed down to the channels of a [ChannelMode]. Returned by [downmix].
+#[derive(Clone)]
+pub struct Downmix<I> {
+    /// Interleaved samples of all source channels.
+    samples: I,
+    /// Number of consecutive source samples, that are averaged into a single one.
+    mixed_samples: usize,
+}
+
+impl<I: Iterator<Item = f32>> Iterator for Downmix<I> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let (sum, count) = self
+            .samples
+            .by_ref()
+            .take(self.mixed_samples)
+            .fold((0.0, 0), |(sum, count), sample| (sum + sample, count + 1));
+        (count > 0).then(|| sum / count as f32)
+    }
+}
+
+/// Mixes the samples down to the channels of the channel mode.
+///
+/// Mono sample is an average of the samples of all channels of a frame, so it never clips.
+///
+/// # Parameters
+///
+/// * `samples` - interleaved samples of all channels.
+/// * `channels` - number of channels of the samples.
+/// * `mode` - channels to mix the samples down to.
+pub fn downmix<I: Iterator<Item = f32>>(
+    samples: I,
+    channels: u16,
+    mode: ChannelMode,
+) -> Downmix<I> {
+    Downmix {
+        samples,
+        mixed_samples: match mode {
+            ChannelMode::Keep => 1,
+            ChannelMode::Mono => usize::from(channels.max(1)),
+        },
+    }
+}
+
+/// Peak level of normalized samples in dBFS, i.e. decibels relative to the full scale.
+///
+/// It is a bit below the full scale, so lossy encoding doesn't clip the peaks.
+pub const NORMALIZE_TARGET_DBFS: f32 = -1.0;
+
+/// Scales the samples, so their peak amplitude hits the target level.
+///
+/// Samples are passed twice: the first pass over a clone of the iterator finds the peak amplitude,
+/// and the second one applies the gain. Silence has no peak, so it is left as is.
+///
+/// # Parameters
+///
+/// * `samples` - interleaved samples of all channels.
+/// * `target_dbfs` - peak level of the scaled samples in dBFS.
+pub fn normalize(
+    samples: impl Iterator<Item = f32> + Clone,
+    target_dbfs: f32,
+) -> impl Iterator<Item = f32> {
+    let peak = samples
+        .clone()
+        .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+    let gain = if peak > 0.0 {
+        10.0_f32.powf(target_dbfs / 20.0) / peak
+    } else {
+        1.0
+    };
+    samples.map(move |sample| sample * gain)
+}
+
+/// Peak level in dBFS, the samples are hard-limited to, see [apply_gain].
+///
+/// It is a bit below the full scale, like [NORMALIZE_TARGET_DBFS], so lossy encoding doesn't clip
+/// the limited peaks once more.
+pub const LIMIT_CEILING_DBFS: f32 = -1.0;
+
+/// Amplifies or attenuates the samples by the gain, optionally hard-limiting them.
+///
+/// Amplified samples can go beyond the full scale. They never overflow 16-bit integers, when they
+/// are written, as both WAV and MP3 writers clip them to the full scale, but clipping flattens the
+/// peaks and is heard as distortion. Hard-limiting clips the samples at [LIMIT_CEILING_DBFS]
+/// instead, so they never reach the full scale, but it distorts the peaks the same way, so it is
+/// meant for occasional peaks only. Lower gain or normalization avoids the distortion entirely.
+///
+/// # Parameters
+///
+/// * `samples` - interleaved samples of all channels.
+/// * `gain_db` - gain in decibels, positive to amplify and negative to attenuate the samples.
+/// * `limit` - whether to hard-limit the amplified samples to [LIMIT_CEILING_DBFS].
+pub fn apply_gain(
+    samples: impl Iterator<Item = f32>,
+    gain_db: f32,
+    limit: bool,
+) -> impl Iterator<Item = f32> {
+    let gain = 10.0_f32.powf(gain_db / 20.0);
+    let ceiling = if limit {
+        10.0_f32.powf(LIMIT_CEILING_DBFS / 20.0)
+    } else {
+        f32::INFINITY
+    };
+    samples.map(move |sample| (sample * gain).clamp(-ceiling, ceiling))
+}
+
+/// Joins the clips one after another, overlapping the end of each clip with the beginning of the
+/// next one, so the former fades out, while the latter fades in.
+///
+/// Gain ramps are linear and complementary, so their sum is always the full volume. Overlap is
+/// clamped to the length of the shorter one of two adjacent clips, so a short clip is faded
+/// entirely instead of being cut.
+///
+/// # Parameters
+///
+/// * `clips` - interleaved samples of all channels of each clip.
+/// * `channels` - number of channels of the clips.
+/// * `crossfade_frames` - number of frames, the adjacent clips overlap by.
+pub fn crossfade(clips: Vec<Vec<f32>>, channels: u16, crossfade_frames: usize) -> Vec<f32> {
+    let channels = usize::from(channels.max(1));
+    let mut joined: Vec<f32> = Vec::new();
+    let mut previous_len = 0;
+    for clip in clips {
+        // Count whole frames, so samples of different channels are never mixed
+        let overlap = crossfade_frames
+            .min(previous_len)
+            .min(clip.len() / channels);
+        let overlap_start = joined.len() - overlap * channels;
+        for (index, (joined_sample, &sample)) in
+            joined[overlap_start..].iter_mut().zip(&clip).enumerate()
+        {
+            let gain = ((index / channels) as f32 + 0.5) / overlap as f32;
+            *joined_sample = *joined_sample * (1.0 - gain) + sample * gain;
+        }
+        joined.extend_from_slice(&clip[overlap * channels..]);
+        previous_len = clip.len() / channels;
+    }
+    joined
+}
+
+/// Removes the range of frames from the samples, joining the parts before and after it with a
+/// crossfade, so there is no click at the join. See [crossfade].
+///
+/// The crossfade overlaps the parts, so the result is shorter than the rest of the samples by the
+/// crossfade too.
+///
+/// # Parameters
+///
+/// * `samples` - interleaved samples of all channels.
+/// * `channels` - number of channels of the samples.
+/// * `frames` - range of the frames to remove. It is cut to the existing frames.
+/// * `crossfade_frames` - number of frames, the parts before and after the range overlap by.
+pub fn remove_range(
+    mut samples: Vec<f32>,
+    channels: u16,
+    frames: std::ops::Range<usize>,
+    crossfade_frames: usize,
+) -> Vec<f32> {
+    let frame_len = usize::from(channels.max(1));
+    let end = (frames.end * frame_len).min(samples.len());
+    let start = (frames.start * frame_len).min(end);
+    let after = samples.split_off(end);
+    samples.truncate(start);
+    crossfade(vec![samples, after], channels, crossfade_frames)
+}
+
+/// Joins the clips one after another with silence between each two adjacent ones.
+///
+/// # Parameters
+///
+/// * `clips` - interleaved samples of all channels of each clip.
+/// * `channels` - number of channels of the clips.
+/// * `gap_frames` - number of silent frames between the adjacent clips.
+pub fn insert_silence(clips: Vec<Vec<f32>>, channels: u16, gap_frames: usize) -> Vec<f32> {
+    let gap = vec![0.0; gap_frames * usize::from(channels)];
+    let mut joined = Vec::new();
+    for (index, clip) in clips.into_iter().enumerate() {
+        if index > 0 {
+            joined.extend_from_slice(&gap);
+        }
+        joined.extend(clip);
+    }
+    joined
+}
+
+/// Number of frames, passed to MP3 encoder at once.
+///
+/// It is a number of frames in a single MP3 frame, so encoder gets whole MP3 frames.
+const MP3_CHUNK_FRAMES: usize = 1152;
+
+/// Bitrate of exported MP3 files.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mp3Bitrate {
+    Kbps128,
+    Kbps192,
+    Kbps320,
+}
+
+impl Mp3Bitrate {
+    /// Returns the bitrate in kilobits per second.
+    pub fn kbps(self) -> u32 {
+        match self {
+            Mp3Bitrate::Kbps128 => 128,
+            Mp3Bitrate::Kbps192 => 192,
+            Mp3Bitrate::Kbps320 => 320,
+        }
+    }
+}
+
+/// Format of exported audio files.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// Uncompressed 16-bit PCM WAV.
+    Wav,
+    /// MP3 with a constant bitrate.
+    Mp3(Mp3Bitrate),
+}
+
+impl ExportFormat {
+    /// File extensions of all export formats.
+    pub const EXTENSIONS: &[&str] = &["wav", "mp3"];
+
+    /// Returns the format of the given file extension, if it is one of [ExportFormat::EXTENSIONS].
+    ///
+    /// MP3 bitrate can't be told from the extension, so the middle one is taken.
+    ///
+    /// # Parameters
+    ///
+    /// * `extension` - file extension without the leading dot, in any case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "wav" => Some(ExportFormat::Wav),
+            "mp3" => Some(ExportFormat::Mp3(Mp3Bitrate::Kbps192)),
+            _ => None,
+        }
+    }
+
+    /// Returns the file extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "wav",
+            ExportFormat::Mp3(_) => "mp3",
+        }
+    }
+}
+
+/// Number of samples, passed between progress updates, so the progress is not locked for each one.
+const PROGRESS_UPDATE_SAMPLES: usize = 64 * 1024;
+
+/// Progress of an export, shared between the thread, that exports, and UI.
+///
+/// It is a share of the written samples from `0.0` to `1.0`. Export of several files reports the
+/// progress of each file as a part of the whole progress, see [ExportProgress::part].
+#[derive(Clone)]
+pub struct ExportProgress {
+    /// Progress of the whole export.
+    shared: std::sync::Arc<std::sync::Mutex<f32>>,
+    /// Progress of the whole export, at which this part of it starts.
+    start: f32,
+    /// Share of the whole export, that this part of it takes.
+    share: f32,
+}
+
+impl Default for ExportProgress {
+    fn default() -> Self {
+        Self {
+            shared: std::sync::Arc::new(std::sync::Mutex::new(0.0)),
+            start: 0.0,
+            share: 1.0,
+        }
+    }
+}
+
+impl ExportProgress {
+    /// Returns progress of the whole export from `0.0` to `1.0`.
+    pub fn get(&self) -> f32 {
+        *self.shared.lock().unwrap()
+    }
+
+    /// Returns progress of one of the equal parts of the export, e.g. of a single file of several
+    /// ones.
+    ///
+    /// # Parameters
+    ///
+    /// * `index` - index of the part.
+    /// * `count` - number of the parts.
+    pub fn part(&self, index: usize, count: usize) -> Self {
+        let share = self.share / count.max(1) as f32;
+        Self {
+            shared: std::sync::Arc::clone(&self.shared),
+            start: self.start + share * index as f32,
+            share,
+        }
+    }
+
+    /// Sets progress of this part of the export.
+    ///
+    /// # Parameters
+    ///
+    /// * `fraction` - progress of this part from `0.0` to `1.0`.
+    fn set(&self, fraction: f32) {
+        *self.shared.lock().unwrap() = self.start + self.share * fraction.clamp(0.0, 1.0);
+    }
+}
+
+/// Iterator over samples, that reports the share of the passed ones as progress of an export.
+/// Returned by [track_progress].
+pub struct TrackProgress<I> {
+    /// Interleaved samples of all channels.
+    samples: I,
+    /// Number of the passed samples.
+    passed: usize,
+    /// Number of all samples.
+    total: usize,
+    /// Where to report the progress to.
+    progress: ExportProgress,
+}
+
+impl<I: Iterator<Item = f32>> Iterator for TrackProgress<I> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let Some(sample) = self.samples.next() else {
+            self.progress.set(1.0);
+            return None;
+        };
+        self.passed += 1;
+        if self.passed.is_multiple_of(PROGRESS_UPDATE_SAMPLES) {
+            self.progress
+                .set(self.passed as f32 / self.total.max(1) as f32);
+        }
+        Some(sample)
+    }
+}
+
+/// Reports the share of the passed samples as progress of an export, as they are written.
+///
+/// # Parameters
+///
+/// * `samples` - interleaved samples of all channels.
+/// * `total` - number of all samples.
+/// * `progress` - where to report the progress to.
+pub fn track_progress<I: Iterator<Item = f32>>(
+    samples: I,
+    total: usize,
+    progress: &ExportProgress,
+) -> TrackProgress<I> {
+    progress.set(0.0);
+    TrackProgress {
+        samples,
+        passed: 0,
+        total,
+        progress: progress.clone(),
+    }
+}
+
+/// Writes the samples to a new audio file of the given format.
+///
+/// The samples are written to a temporary file next to the destination, which replaces the
+/// destination only once it is written completely. So an export, that fails or is cut short by
+/// closing the application, never leaves a truncated file or corrupts an existing one.
+///
+/// # Parameters
+///
+/// * `path` - path to the file to write. Existing file is overwritten.
+/// * `samples` - interleaved samples of all channels.
+/// * `sample_rate` - number of frames per second.
+/// * `channels` - number of channels.
+/// * `format` - format of the file.
+pub fn write(
+    path: &Path,
+    samples: impl Iterator<Item = f32>,
+    sample_rate: u32,
+    channels: u16,
+    format: ExportFormat,
+) -> Result<(), String> {
+    let temp_path = temp_path(path);
+    let written = match format {
+        ExportFormat::Wav => {
+            write_wav(&temp_path, samples, sample_rate, channels).map_err(|error| error.to_string())
+        }
+        ExportFormat::Mp3(bitrate) => encode_mp3(samples, sample_rate, channels, bitrate)
+            .and_then(|mp3| std::fs::write(&temp_path, mp3).map_err(|error| error.to_string())),
+    };
+    // Renaming within the same directory replaces the destination at once
+    let result =
+        written.and_then(|_| std::fs::rename(&temp_path, path).map_err(|error| error.to_string()));
+    if result.is_err() {
+        // The temporary file may not even be created, nothing to clean up then
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Returns path to the hidden temporary file, the file at the given path is written to, e.g.
+/// `.region.wav.part` for `region.wav`.
+///
+/// # Parameters
+///
+/// * `path` - path to the file to write.
+fn temp_path(path: &Path) -> std::path::PathBuf {
+    let mut file_name = std::ffi::OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(".part");
+    path.with_file_name(file_name)
+}
+
+/// Encodes the samples to MP3 with a constant bitrate.
+///
+/// Samples outside of the `-1.0..=1.0` range are clipped.
+///
+/// # Parameters
+///
+/// * `samples` - interleaved samples of all channels.
+/// * `sample_rate` - number of frames per second.
+/// * `channels` - number of channels. MP3 supports mono and stereo only.
+/// * `bitrate` - bitrate of the encoded MP3.
+pub fn encode_mp3(
+    samples: impl Iterator<Item = f32>,
+    sample_rate: u32,
+    channels: u16,
+    bitrate: Mp3Bitrate,
+) -> Result<Vec<u8>, String> {
+    if channels != 1 && channels != 2 {
+        return Err(tr_args(Key::Mp3ChannelsUnsupported, &[&channels]));
+    }
+
+    let mut builder = mp3lame_encoder::Builder::new().ok_or(tr(Key::Mp3EncoderFailed))?;
+    builder
+        .set_num_channels(channels as u8)
+        .map_err(|error| error.to_string())?;
+    builder
+        .set_sample_rate(sample_rate)
+        .map_err(|error| error.to_string())?;
+    builder
+        .set_brate(match bitrate {
+            Mp3Bitrate::Kbps128 => mp3lame_encoder::Bitrate::Kbps128,
+            Mp3Bitrate::Kbps192 => mp3lame_encoder::Bitrate::Kbps192,
+            Mp3Bitrate::Kbps320 => mp3lame_encoder::Bitrate::Kbps320,
+        })
+        .map_err(|error| error.to_string())?;
+    builder
+        .set_quality(mp3lame_encoder::Quality::Good)
+        .map_err(|error| error.to_string())?;
+    let mut encoder = builder.build().map_err(|error| error.to_string())?;
+
+    let mut mp3 = Vec::new();
+    let mut samples = samples.map(|sample| sample.clamp(-1.0, 1.0)).peekable();
+    let chunk_len = MP3_CHUNK_FRAMES * usize::from(channels);
+    while samples.peek().is_some() {
+        let chunk: Vec<f32> = samples.by_ref().take(chunk_len).collect();
+        mp3.reserve(mp3lame_encoder::max_required_buffer_size(
+            chunk.len() / usize::from(channels),
+        ));
+        // Encoder takes interleaved samples for stereo only
+        let encoded = if channels == 1 {
+            encoder.encode_to_vec(mp3lame_encoder::MonoPcm(&chunk), &mut mp3)
+        } else {
+            encoder.encode_to_vec(mp3lame_encoder::InterleavedPcm(&chunk), &mut mp3)
+        };
+        encoded.map_err(|error| error.to_string())?;
+    }
+
+    // Flushing writes the last MP3 frame, that needs at most this number of bytes
+    mp3.reserve(7200);
+    encoder
+        .flush_to_vec::<mp3lame_encoder::FlushNoGap>(&mut mp3)
+        .map_err(|error| error.to_string())?;
+    Ok(mp3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that the samples are equal within the rounding error of `f32` math.
+    ///
+    /// # Parameters
+    ///
+    /// * `actual` - the samples to check.
+    /// * `expected` - the expected samples.
+    fn assert_samples(actual: &[f32], expected: &[f32]) {
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "{actual:?} is not {expected:?}"
+        );
+        for (actual_sample, expected_sample) in actual.iter().zip(expected) {
+            assert!(
+                (actual_sample - expected_sample).abs() < 1e-5,
+                "{actual:?} is not {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn normalize_scales_peak_to_target() {
+        let samples = [0.1, -0.25, 0.2];
+
+        let normalized: Vec<f32> = normalize(samples.into_iter(), -6.0).collect();
+
+        let target = 10.0_f32.powf(-6.0 / 20.0);
+        let gain = target / 0.25;
+        assert_samples(&normalized, &[0.1 * gain, -target, 0.2 * gain]);
+    }
+
+    #[test]
+    fn normalize_leaves_silence_as_is() {
+        let normalized: Vec<f32> = normalize([0.0; 4].into_iter(), NORMALIZE_TARGET_DBFS).collect();
+
+        assert_eq!(normalized, [0.0; 4]);
+    }
+
+    #[test]
+    fn apply_gain_limits_amplified_samples() {
+        let samples = [0.1, -0.5, 0.9, -1.0];
+
+        let amplified: Vec<f32> = apply_gain(samples.into_iter(), 12.0, true).collect();
+
+        let gain = 10.0_f32.powf(12.0 / 20.0);
+        let ceiling = 10.0_f32.powf(LIMIT_CEILING_DBFS / 20.0);
+        assert!(amplified.iter().all(|sample| sample.abs() <= ceiling));
+        assert_samples(&amplified, &[0.1 * gain, -ceiling, ceiling, -ceiling]);
+    }
+
+    #[test]
+    fn apply_gain_scales_samples_without_limit() {
+        let samples = [0.1, -0.5, 0.9, -1.0];
+
+        let amplified: Vec<f32> = apply_gain(samples.into_iter(), 12.0, false).collect();
+
+        let gain = 10.0_f32.powf(12.0 / 20.0);
+        assert_eq!(amplified, samples.map(|sample| sample * gain));
+    }
+
+    #[test]
+    fn downmix_averages_stereo_to_mono() {
+        let samples = [0.2, 0.4, -0.5, 0.5, 1.0, 0.0];
+
+        let mono: Vec<f32> = downmix(samples.into_iter(), 2, ChannelMode::Mono).collect();
+
+        assert_samples(&mono, &[0.3, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn downmix_keeps_channels_as_is() {
+        let samples = [0.2, 0.4, -0.5, 0.5];
+
+        let kept: Vec<f32> = downmix(samples.into_iter(), 2, ChannelMode::Keep).collect();
+
+        assert_eq!(kept, samples);
+    }
+
+    #[test]
+    fn downmix_averages_trailing_partial_frame() {
+        let samples = [0.2, 0.4, 0.6];
+
+        let mono: Vec<f32> = downmix(samples.into_iter(), 2, ChannelMode::Mono).collect();
+
+        assert_samples(&mono, &[0.3, 0.6]);
+    }
+
+    #[test]
+    fn crossfade_clamps_overlap_to_shorter_clip() {
+        let joined = crossfade(vec![vec![1.0; 4], vec![0.0; 2]], 1, 10);
+        assert_samples(&joined, &[1.0, 1.0, 0.75, 0.25]);
+
+        let joined = crossfade(vec![vec![1.0; 2], vec![0.0; 4]], 1, 10);
+        assert_samples(&joined, &[0.75, 0.25, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn crossfade_mixes_frames_channel_by_channel() {
+        let joined = crossfade(vec![vec![1.0, -1.0, 1.0, -1.0], vec![0.0; 4]], 2, 1);
+
+        assert_samples(&joined, &[1.0, -1.0, 0.5, -0.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn crossfade_shortens_by_overlaps() {
+        let joined = crossfade(vec![vec![0.5; 5], vec![0.5; 6], vec![0.5; 7]], 1, 2);
+
+        assert_eq!(joined.len(), 5 + 6 + 7 - 2 * 2);
+    }
+
+    #[test]
+    fn crossfade_passes_empty_clip() {
+        let joined = crossfade(vec![vec![1.0; 3], Vec::new(), vec![0.0; 3]], 1, 2);
+
+        // Nothing overlaps an empty clip, so its neighbours are joined back to back
+        assert_samples(&joined, &[1.0, 1.0, 1.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn insert_silence_separates_clips_only() {
+        let joined = insert_silence(vec![vec![1.0; 4], vec![0.5; 2], vec![0.25; 4]], 2, 3);
+
+        let gap = [0.0; 3 * 2];
+        let expected: Vec<f32> = [&[1.0; 4][..], &gap, &[0.5; 2], &gap, &[0.25; 4]].concat();
+        assert_eq!(joined, expected);
+    }
+
+    #[test]
+    fn remove_range_at_start() {
+        let rest = remove_range(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 1, 0..2, 0);
+
+        assert_eq!(rest, [3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn remove_range_at_end() {
+        let rest = remove_range(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 2..3, 0);
+
+        assert_eq!(rest, [1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn remove_range_past_end() {
+        let samples = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+
+        assert_eq!(
+            remove_range(samples.clone(), 1, 4..10, 0),
+            [1.0, 2.0, 3.0, 4.0]
+        );
+        assert_eq!(remove_range(samples.clone(), 1, 8..10, 0), samples);
+    }
+
+    #[test]
+    fn remove_range_clamps_crossfade_to_rest() {
+        let rest = remove_range(vec![1.0, 1.0, 0.5, 0.5, 0.5, 0.0], 1, 2..5, 5);
+
+        // Only a single frame is left after the range, so only it overlaps
+        assert_samples(&rest, &[1.0, 0.5]);
+    }
+
+    #[test]
+    fn failed_write_keeps_existing_file() {
+        let path =
+            std::env::temp_dir().join(format!("audio_cutter_{}_existing.mp3", std::process::id()));
+        std::fs::write(&path, b"existing").unwrap();
+
+        // MP3 doesn't support 3 channels
+        let result = write(
+            &path,
+            [0.0; 6].into_iter(),
+            8000,
+            3,
+            ExportFormat::Mp3(Mp3Bitrate::Kbps128),
+        );
+
+        let contents = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+        assert_eq!(contents, b"existing");
+        assert!(!temp_path(&path).exists());
+    }
+}
@@ -0,0 +1,607 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Language of the user interface, used by [tr] and [tr_args].
+///
+/// It is English, until another one is set, so the library functions, used without the UI, e.g.
+/// [crate::cut_file], return English error messages. The application sets the chosen or the
+/// system language on start.
+static CURRENT_LANG: AtomicU8 = AtomicU8::new(Lang::English as u8);
+
+/// Languages of the user interface.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Lang {
+    English,
+    Russian,
+}
+
+impl Lang {
+    /// All supported languages in the order they are offered to user.
+    pub const ALL: [Lang; 2] = [Lang::English, Lang::Russian];
+
+    /// Returns the language name, written in the language itself, so user finds the native one
+    /// whatever the current language is.
+    pub fn name(self) -> &'static str {
+        match self {
+            Lang::English => "English",
+            Lang::Russian => "Русский",
+        }
+    }
+
+    /// Returns the ISO 639-1 code of the language, e.g. for storing it.
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::English => "en",
+            Lang::Russian => "ru",
+        }
+    }
+
+    /// Returns the language with the given ISO 639-1 code, if it is supported.
+    ///
+    /// # Parameters
+    ///
+    /// * `code` - the language code, e.g. returned by [Lang::code].
+    pub fn from_code(code: &str) -> Option<Lang> {
+        Lang::ALL.into_iter().find(|lang| lang.code() == code)
+    }
+
+    /// Returns the language of the system locale, or English if it is not supported.
+    ///
+    /// The locale is taken from the POSIX locale environment variables, in the order of their
+    /// precedence, e.g. `ru_RU.UTF-8`.
+    pub fn system() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|variable| std::env::var(variable).ok())
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| Lang::from_code(locale.get(..2)?))
+            .unwrap_or(Lang::English)
+    }
+}
+
+/// Returns the current language of the user interface.
+pub fn lang() -> Lang {
+    if CURRENT_LANG.load(Ordering::Relaxed) == Lang::Russian as u8 {
+        Lang::Russian
+    } else {
+        Lang::English
+    }
+}
+
+/// Sets the language of the user interface, that all further texts are translated to.
+///
+/// # Parameters
+///
+/// * `lang` - the new language.
+pub fn set_lang(lang: Lang) {
+    CURRENT_LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// Keys of user-facing texts.
+///
+/// Texts with `{}` placeholders are formatted by [tr_args].
+#[derive(Clone, Copy)]
+pub enum Key {
+    OpenFile,
+    AudioFilesFilter,
+    FileFilter,
+    DropHint,
+    UnsupportedFile,
+    RecentFiles,
+    OpenedFile,
+    CopyFullPath,
+    RevealInFileManager,
+    FileNotFound,
+    RevealFailed,
+    FullPath,
+    FileDetails,
+    SampleRate,
+    Channels,
+    Mono,
+    Stereo,
+    AverageBitrate,
+    StreamedFromDisk,
+    RegionFrom,
+    RegionTo,
+    Reset,
+    LoopRegion,
+    LoopRegionHint,
+    PlayRegionOnly,
+    PreviewOriginal,
+    PreviewOriginalHint,
+    PreviewProcessed,
+    PreviewProcessedHint,
+    FadeIn,
+    FadeOut,
+    Normalize,
+    NormalizeHint,
+    Gain,
+    GainHint,
+    Limit,
+    LimitHint,
+    Format,
+    Mp3Format,
+    ExportChannels,
+    KeepChannels,
+    SaveRegion,
+    SaveWithoutRegion,
+    SaveWithoutRegionHint,
+    RegionDuration,
+    RegionStartAfterEnd,
+    RegionPastTrackEnd,
+    AddToList,
+    SaveAllRegions,
+    SaveJoinedRegions,
+    Crossfade,
+    CrossfadeHint,
+    Silence,
+    SilenceHint,
+    SplitAtSilences,
+    SplitAtSilencesHint,
+    SilenceThreshold,
+    SilenceThresholdHint,
+    MinSilence,
+    MinSilenceHint,
+    NoSoundFound,
+    RegionListItem,
+    Edit,
+    EditHint,
+    RemoveHint,
+    RegionFileName,
+    CutOutFileName,
+    JoinedFileName,
+    RegionsSaved,
+    JoinedRegionsSaved,
+    RegionsSaveFailed,
+    RegionSaved,
+    CutOutSaved,
+    RegionSaveFailed,
+    ExportInterrupted,
+    ExportOverSource,
+    CopyFrameIndex,
+    CopySampleIndex,
+    Play,
+    Pause,
+    Stop,
+    PlayOnce,
+    RepeatTrack,
+    Volume,
+    Mute,
+    Unmute,
+    Speed,
+    Level,
+    SinkEmpty,
+    SinkPaused,
+    SinkPlaying,
+    SinkState,
+    SinkPosition,
+    SinkQueued,
+    SinkVolume,
+    Settings,
+    Language,
+    UiScale,
+    UiScaleHint,
+    LoadMode,
+    LoadModeAuto,
+    LoadModeMemory,
+    LoadModeStreamed,
+    LoadModeHint,
+    DebugInfo,
+    UpdateInterval,
+    UpdateIntervalHint,
+    HiddenUpdateInterval,
+    HiddenUpdateIntervalHint,
+    ResumeFromPause,
+    JumpToStart,
+    JumpToEnd,
+    CloseTab,
+    ZoomIn,
+    ZoomOut,
+    ZoomToFit,
+    ZoomHint,
+    WaveformView,
+    SpectrogramView,
+    SpectrogramHint,
+    ComputingSpectrogram,
+    SetToPosition,
+    SecondsSuffix,
+    TimestampHint,
+    InvalidTimestamp,
+    MillisecondsSuffix,
+    DecibelsSuffix,
+    OutputOpenFailed,
+    DeviceLost,
+    Mp3ChannelsUnsupported,
+    Mp3EncoderFailed,
+    LoadFailed,
+    FileOpenFailed,
+    UnrecognizedFormat,
+    UnsupportedCodec,
+    DecodeFailed,
+    SampleRateChanges,
+}
+
+/// Returns the text of the key in the current language.
+///
+/// # Parameters
+///
+/// * `key` - key of the text.
+pub fn tr(key: Key) -> &'static str {
+    match lang() {
+        Lang::English => english(key),
+        Lang::Russian => russian(key),
+    }
+}
+
+/// Returns the text of the key in the current language with its `{}` placeholders replaced by the
+/// given arguments in order.
+///
+/// # Parameters
+///
+/// * `key` - key of the text.
+/// * `args` - values for the placeholders. Format them beforehand, if e.g. a precision is needed.
+pub fn tr_args(key: Key, args: &[&dyn std::fmt::Display]) -> String {
+    let mut parts = tr(key).split("{}");
+    let mut text = String::from(parts.next().unwrap_or_default());
+    for (part, arg) in parts.zip(args) {
+        text.push_str(&arg.to_string());
+        text.push_str(part);
+    }
+    text
+}
+
+/// Returns the English text of the key.
+///
+/// # Parameters
+///
+/// * `key` - key of the text.
+fn english(key: Key) -> &'static str {
+    match key {
+        Key::OpenFile => "Open file...",
+        Key::AudioFilesFilter => "Audio files",
+        Key::FileFilter => "{} file",
+        Key::DropHint => "Drop the file to open it",
+        Key::UnsupportedFile => "File {} is not supported. Supported files are {}",
+        Key::RecentFiles => "Recent files",
+        Key::OpenedFile => "Opened file:",
+        Key::CopyFullPath => "Copy full path",
+        Key::RevealInFileManager => "Show in file manager",
+        Key::FileNotFound => "File {} is not found, it could be moved or removed",
+        Key::RevealFailed => "Failed to open file manager: {}",
+        Key::FullPath => "Full path",
+        Key::FileDetails => "File info",
+        Key::SampleRate => "Sample rate: {} Hz",
+        Key::Channels => "Channels: {}",
+        Key::Mono => "Mono",
+        Key::Stereo => "Stereo",
+        Key::AverageBitrate => "Average bitrate: {} kbps",
+        Key::StreamedFromDisk => "Streamed from disk to save memory",
+        Key::RegionFrom => "Region from",
+        Key::RegionTo => "to",
+        Key::Reset => "Reset",
+        Key::LoopRegion => "Loop region",
+        Key::LoopRegionHint => "Play the region over and over, e.g. to practice a passage",
+        Key::PlayRegionOnly => "Play region only",
+        Key::PreviewOriginal => "A: original",
+        Key::PreviewOriginalHint => "Play the original audio",
+        Key::PreviewProcessed => "B: processed",
+        Key::PreviewProcessedHint => {
+            "Play the region with fades, normalization and channels, chosen for export"
+        }
+        Key::FadeIn => "Fade in",
+        Key::FadeOut => "Fade out",
+        Key::Normalize => "Normalize",
+        Key::NormalizeHint => "Amplify or attenuate the region, so its peak is at {} dBFS",
+        Key::Gain => "Gain:",
+        Key::GainHint => "Amplify or attenuate the region, after normalization, if it is on",
+        Key::Limit => "Limit",
+        Key::LimitHint => {
+            "Keep amplified peaks below {} dBFS by cutting them, which distorts them a bit"
+        }
+        Key::Format => "Format:",
+        Key::Mp3Format => "MP3 {} kbps",
+        Key::ExportChannels => "Channels:",
+        Key::KeepChannels => "As in the source",
+        Key::SaveRegion => "Save region",
+        Key::SaveWithoutRegion => "Save without region",
+        Key::SaveWithoutRegionHint => {
+            "Cut the region out and save the rest of the track, joining the parts around it"
+        }
+        Key::RegionDuration => "Region duration: {} s",
+        Key::RegionStartAfterEnd => "Region start must be before its end",
+        Key::RegionPastTrackEnd => "Region end must not exceed the audio track duration of {} s",
+        Key::AddToList => "Add to list",
+        Key::SaveAllRegions => "Save all regions",
+        Key::SaveJoinedRegions => "Save regions joined",
+        Key::Crossfade => "Crossfade",
+        Key::CrossfadeHint => "Overlap the end of each region with the beginning of the next one",
+        Key::Silence => "Silence",
+        Key::SilenceHint => "Separate the regions with silence",
+        Key::SplitAtSilences => "Split at silences",
+        Key::SplitAtSilencesHint => {
+            "Replace the listed regions with the parts of the track, separated by silences"
+        }
+        Key::SilenceThreshold => "Threshold:",
+        Key::SilenceThresholdHint => "Sound, quieter than this level, is silence",
+        Key::MinSilence => "Shortest silence:",
+        Key::MinSilenceHint => "Shorter pauses, e.g. between words, don't split the track",
+        Key::NoSoundFound => "Nothing but silence is found in the track",
+        Key::RegionListItem => "{}. {} s – {} s",
+        Key::Edit => "Edit",
+        Key::EditHint => "Remove from the list for editing",
+        Key::RemoveHint => "Remove from the list",
+        Key::RegionFileName => "region",
+        Key::CutOutFileName => "cut",
+        Key::JoinedFileName => "joined",
+        Key::RegionsSaved => "Regions saved: {} to {}",
+        Key::JoinedRegionsSaved => "Joined regions saved: {}",
+        Key::RegionsSaveFailed => "Failed to save regions: {}",
+        Key::RegionSaved => "Region saved: {}",
+        Key::CutOutSaved => "Track without region saved: {}",
+        Key::RegionSaveFailed => "Failed to save region: {}",
+        Key::ExportInterrupted => "Export was interrupted unexpectedly",
+        Key::ExportOverSource => "the opened audio file can't be overwritten, choose another file",
+        Key::CopyFrameIndex => "Copy frame #",
+        Key::CopySampleIndex => "Copy sample #",
+        Key::Play => "Play",
+        Key::Pause => "Pause",
+        Key::Stop => "Stop",
+        Key::PlayOnce => "Play once",
+        Key::RepeatTrack => "Repeat track",
+        Key::Volume => "Volume:",
+        Key::Mute => "Mute",
+        Key::Unmute => "Unmute",
+        Key::Speed => "Speed:",
+        Key::Level => "Level:",
+        Key::SinkEmpty => "empty",
+        Key::SinkPaused => "paused",
+        Key::SinkPlaying => "playing",
+        Key::SinkState => "State: {}",
+        Key::SinkPosition => "Position: {} s",
+        Key::SinkQueued => "Queued: {}",
+        Key::SinkVolume => "Volume: {}",
+        Key::Settings => "Settings",
+        Key::Language => "Language:",
+        Key::UiScale => "Interface scale:",
+        Key::UiScaleHint => "Also changed with Ctrl+Plus, Ctrl+Minus and reset with Ctrl+0",
+        Key::LoadMode => "Decoded audio:",
+        Key::LoadModeAuto => "Automatically",
+        Key::LoadModeMemory => "Kept in memory",
+        Key::LoadModeStreamed => "Streamed from disk",
+        Key::LoadModeHint => {
+            "Files over {} MiB are streamed from disk automatically, as they could exhaust memory \
+            once decoded. Streaming takes more CPU time. Applies to files, opened afterwards"
+        }
+        Key::DebugInfo => "Debug information",
+        Key::UpdateInterval => "Position updates:",
+        Key::UpdateIntervalHint => {
+            "The more often the updates, the smoother the position moves, \
+             but the more power is used on redrawing the window"
+        }
+        Key::HiddenUpdateInterval => "Updates while minimized:",
+        Key::HiddenUpdateIntervalHint => {
+            "The rarer the updates, the less power is used, \
+             but the more outdated the position is, when the window is shown again"
+        }
+        Key::ResumeFromPause => "Resume from the pause position",
+        Key::JumpToStart => "Jump to the beginning (Home)",
+        Key::JumpToEnd => "Jump to the end (End)",
+        Key::CloseTab => "Close the file",
+        Key::ZoomIn => "Zoom in",
+        Key::ZoomOut => "Zoom out",
+        Key::ZoomToFit => "Whole track",
+        Key::ZoomHint => "×{} (mouse wheel zooms, Shift+wheel scrolls)",
+        Key::WaveformView => "Waveform",
+        Key::SpectrogramView => "Spectrogram",
+        Key::SpectrogramHint => {
+            "Levels of frequencies over time, from low ones at the bottom to high ones at the top"
+        }
+        Key::ComputingSpectrogram => "Computing the spectrogram...",
+        Key::SetToPosition => "Set to the current position",
+        Key::SecondsSuffix => " s",
+        Key::TimestampHint => "mm:ss.mmm",
+        Key::InvalidTimestamp => "Expected a time in mm:ss.mmm format, not exceeding {}",
+        Key::MillisecondsSuffix => " ms",
+        Key::DecibelsSuffix => " dB",
+        Key::OutputOpenFailed => {
+            "Failed to open the audio output device, playback is unavailable: {}"
+        }
+        Key::DeviceLost => "Audio device lost. Playback is stopped, it can be resumed",
+        Key::Mp3ChannelsUnsupported => {
+            "MP3 supports mono and stereo only, but there are {} channels"
+        }
+        Key::Mp3EncoderFailed => "Failed to create MP3 encoder",
+        Key::LoadFailed => "Couldn't load {}. {}",
+        Key::FileOpenFailed => "Failed to open file: {}",
+        Key::UnrecognizedFormat => {
+            "File format is not recognized, e.g. it is not an audio file. Supported files are {}"
+        }
+        Key::UnsupportedCodec => {
+            "File format or codec is not supported. Supported files are {}, \
+             with OGG files of Vorbis codec only, but not Opus"
+        }
+        Key::DecodeFailed => "Failed to decode file: {}",
+        Key::SampleRateChanges => {
+            "Sample rate changes within the file ({} Hz -> {} Hz), such files are not supported"
+        }
+    }
+}
+
+/// Returns the Russian text of the key.
+///
+/// # Parameters
+///
+/// * `key` - key of the text.
+fn russian(key: Key) -> &'static str {
+    match key {
+        Key::OpenFile => "Открыть файл...",
+        Key::AudioFilesFilter => "Аудио файлы",
+        Key::FileFilter => "{} файл",
+        Key::DropHint => "Отпустите файл, чтобы открыть его",
+        Key::UnsupportedFile => "Файл {} не поддерживается. Поддерживаются файлы {}",
+        Key::RecentFiles => "Недавние файлы",
+        Key::OpenedFile => "Открытый файл:",
+        Key::CopyFullPath => "Копировать полный путь",
+        Key::RevealInFileManager => "Показать в файловом менеджере",
+        Key::FileNotFound => "Файл {} не найден, возможно, он перемещён или удалён",
+        Key::RevealFailed => "Не удалось открыть файловый менеджер: {}",
+        Key::FullPath => "Полный путь",
+        Key::FileDetails => "Информация о файле",
+        Key::SampleRate => "Частота дискретизации: {} Гц",
+        Key::Channels => "Каналов: {}",
+        Key::Mono => "Моно",
+        Key::Stereo => "Стерео",
+        Key::AverageBitrate => "Средний битрейт: {} кбит/с",
+        Key::StreamedFromDisk => "Читается с диска для экономии памяти",
+        Key::RegionFrom => "Фрагмент с",
+        Key::RegionTo => "по",
+        Key::Reset => "Сбросить",
+        Key::LoopRegion => "Повторять фрагмент",
+        Key::LoopRegionHint => {
+            "Проигрывать фрагмент снова и снова, например, чтобы разучить отрывок"
+        }
+        Key::PlayRegionOnly => "Играть только фрагмент",
+        Key::PreviewOriginal => "A: оригинал",
+        Key::PreviewOriginalHint => "Проигрывать исходный звук",
+        Key::PreviewProcessed => "B: обработка",
+        Key::PreviewProcessedHint => {
+            "Проигрывать фрагмент с плавным началом и окончанием, нормализацией и каналами, \
+             выбранными для сохранения"
+        }
+        Key::FadeIn => "Плавное начало",
+        Key::FadeOut => "Плавное окончание",
+        Key::Normalize => "Нормализовать",
+        Key::NormalizeHint => "Усилить или ослабить фрагмент, чтобы его пик был на уровне {} дБFS",
+        Key::Gain => "Усиление:",
+        Key::GainHint => "Усилить или ослабить фрагмент после нормализации, если она включена",
+        Key::Limit => "Ограничить",
+        Key::LimitHint => "Держать усиленные пики ниже {} дБFS, срезая их, что их немного искажает",
+        Key::Format => "Формат:",
+        Key::Mp3Format => "MP3 {} кбит/с",
+        Key::ExportChannels => "Каналы:",
+        Key::KeepChannels => "Как в исходном",
+        Key::SaveRegion => "Сохранить фрагмент",
+        Key::SaveWithoutRegion => "Сохранить без фрагмента",
+        Key::SaveWithoutRegionHint => {
+            "Вырезать фрагмент и сохранить остальной трек, соединив части вокруг него"
+        }
+        Key::RegionDuration => "Длительность фрагмента: {} с",
+        Key::RegionStartAfterEnd => "Начало фрагмента должно быть раньше его конца",
+        Key::RegionPastTrackEnd => "Конец фрагмента не должен превышать длительность трека {} с",
+        Key::AddToList => "Добавить в список",
+        Key::SaveAllRegions => "Сохранить все фрагменты",
+        Key::SaveJoinedRegions => "Сохранить фрагменты вместе",
+        Key::Crossfade => "Плавный переход",
+        Key::CrossfadeHint => "Наложить конец каждого фрагмента на начало следующего",
+        Key::Silence => "Тишина",
+        Key::SilenceHint => "Разделить фрагменты тишиной",
+        Key::SplitAtSilences => "Разделить по тишине",
+        Key::SplitAtSilencesHint => {
+            "Заменить фрагменты в списке частями трека, разделёнными тишиной"
+        }
+        Key::SilenceThreshold => "Порог:",
+        Key::SilenceThresholdHint => "Звук тише этого уровня считается тишиной",
+        Key::MinSilence => "Кратчайшая тишина:",
+        Key::MinSilenceHint => "Более короткие паузы, например между словами, не разделяют трек",
+        Key::NoSoundFound => "В треке не найдено ничего, кроме тишины",
+        Key::RegionListItem => "{}. {} с – {} с",
+        Key::Edit => "Изменить",
+        Key::EditHint => "Убрать из списка для изменения",
+        Key::RemoveHint => "Удалить из списка",
+        Key::RegionFileName => "фрагмент",
+        Key::CutOutFileName => "вырезано",
+        Key::JoinedFileName => "вместе",
+        Key::RegionsSaved => "Сохранено фрагментов: {} в {}",
+        Key::JoinedRegionsSaved => "Объединённые фрагменты сохранены: {}",
+        Key::RegionsSaveFailed => "Не удалось сохранить фрагменты: {}",
+        Key::RegionSaved => "Фрагмент сохранён: {}",
+        Key::CutOutSaved => "Трек без фрагмента сохранён: {}",
+        Key::RegionSaveFailed => "Не удалось сохранить фрагмент: {}",
+        Key::ExportInterrupted => "Сохранение неожиданно прервалось",
+        Key::ExportOverSource => "нельзя перезаписать открытый аудиофайл, выберите другой файл",
+        Key::CopyFrameIndex => "Копировать № кадра",
+        Key::CopySampleIndex => "Копировать № сэмпла",
+        Key::Play => "Играть",
+        Key::Pause => "Пауза",
+        Key::Stop => "Стоп",
+        Key::PlayOnce => "Играть один раз",
+        Key::RepeatTrack => "Повторять трек",
+        Key::Volume => "Громкость:",
+        Key::Mute => "Выключить звук",
+        Key::Unmute => "Включить звук",
+        Key::Speed => "Скорость:",
+        Key::Level => "Уровень:",
+        Key::SinkEmpty => "пусто",
+        Key::SinkPaused => "пауза",
+        Key::SinkPlaying => "воспроизведение",
+        Key::SinkState => "Состояние: {}",
+        Key::SinkPosition => "Позиция: {} с",
+        Key::SinkQueued => "В очереди: {}",
+        Key::SinkVolume => "Громкость: {}",
+        Key::Settings => "Настройки",
+        Key::Language => "Язык:",
+        Key::UiScale => "Масштаб интерфейса:",
+        Key::UiScaleHint => {
+            "Также меняется сочетаниями Ctrl+Плюс, Ctrl+Минус и сбрасывается Ctrl+0"
+        }
+        Key::LoadMode => "Декодированный звук:",
+        Key::LoadModeAuto => "Автоматически",
+        Key::LoadModeMemory => "Хранится в памяти",
+        Key::LoadModeStreamed => "Читается с диска",
+        Key::LoadModeHint => {
+            "Файлы больше {} МиБ автоматически читаются с диска, так как после декодирования могут \
+            занять всю память. Чтение с диска нагружает процессор. Применяется к файлам, открытым \
+            после изменения"
+        }
+        Key::DebugInfo => "Отладочная информация",
+        Key::UpdateInterval => "Обновление позиции:",
+        Key::UpdateIntervalHint => {
+            "Чем чаще обновления, тем плавнее движется позиция, \
+             но тем больше энергии тратится на перерисовку окна"
+        }
+        Key::HiddenUpdateInterval => "Обновление в свёрнутом окне:",
+        Key::HiddenUpdateIntervalHint => {
+            "Чем реже обновления, тем меньше расход энергии, \
+             но тем сильнее отстаёт позиция, когда окно снова показано"
+        }
+        Key::ResumeFromPause => "Продолжить с места паузы",
+        Key::JumpToStart => "Перейти в начало (Home)",
+        Key::JumpToEnd => "Перейти в конец (End)",
+        Key::CloseTab => "Закрыть файл",
+        Key::ZoomIn => "Увеличить",
+        Key::ZoomOut => "Уменьшить",
+        Key::ZoomToFit => "Весь трек",
+        Key::ZoomHint => "×{} (колесо мыши масштабирует, Shift+колесо прокручивает)",
+        Key::WaveformView => "Волна",
+        Key::SpectrogramView => "Спектрограмма",
+        Key::SpectrogramHint => "Уровни частот во времени, от низких внизу до высоких вверху",
+        Key::ComputingSpectrogram => "Вычисляется спектрограмма...",
+        Key::SetToPosition => "Установить на текущую позицию",
+        Key::SecondsSuffix => " с",
+        Key::TimestampHint => "мм:сс.ммм",
+        Key::InvalidTimestamp => "Ожидается время в формате мм:сс.ммм, не больше {}",
+        Key::MillisecondsSuffix => " мс",
+        Key::DecibelsSuffix => " дБ",
+        Key::OutputOpenFailed => {
+            "Не удалось открыть устройство вывода звука, воспроизведение недоступно: {}"
+        }
+        Key::DeviceLost => {
+            "Устройство вывода звука отключено. Воспроизведение остановлено, его можно продолжить"
+        }
+        Key::Mp3ChannelsUnsupported => "MP3 поддерживает только моно и стерео, а каналов: {}",
+        Key::Mp3EncoderFailed => "Не удалось создать MP3 кодировщик",
+        Key::LoadFailed => "Не удалось загрузить {}. {}",
+        Key::FileOpenFailed => "Не удалось открыть файл: {}",
+        Key::UnrecognizedFormat => {
+            "Формат файла не распознан, например, это не аудиофайл. Поддерживаются файлы {}"
+        }
+        Key::UnsupportedCodec => {
+            "Формат или кодек файла не поддерживается. Поддерживаются файлы {}, \
+             причём OGG файлы только с кодеком Vorbis, но не Opus"
+        }
+        Key::DecodeFailed => "Не удалось декодировать файл: {}",
+        Key::SampleRateChanges => {
+            "Частота дискретизации меняется внутри файла ({} Гц -> {} Гц), \
+             такие файлы не поддерживаются"
+        }
+    }
+}
@@ -1,5 +1,93 @@
+pub mod analysis;
 pub mod audio_cutter_app;
 mod audio_thread;
+pub mod export;
+pub mod i18n;
+pub mod pipeline;
+pub mod spectrogram;
+pub mod waveform;
+
+use rodio::Source;
+
+/// Extensions of audio files, that can be decoded and played.
+///
+/// These are formats, decoded by [rodio::Decoder] with its default features.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["mp3", "wav", "flac", "ogg"];
+
+/// Path to the audio file for tests: 1.5 seconds of 8 kHz stereo audio track, that is a 440 Hz
+/// tone for the first and the last half a second, and silence between them.
+#[cfg(test)]
+const TEST_TRACK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tone.wav");
+
+/// Path to a text file for tests, that is named as an MP3 file, but is not an audio file.
+#[cfg(test)]
+const NOT_AUDIO_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/not_audio.mp3");
+
+/// Cuts the region out of the audio file and writes it to a new audio file, without any UI.
+///
+/// The region is written as is, keeping the sample rate and channels of the audio track. Format
+/// of the written file is chosen by its extension, one of [export::ExportFormat::EXTENSIONS].
+///
+/// # Parameters
+///
+/// * `input` - path to the audio file to cut, one of [SUPPORTED_EXTENSIONS].
+/// * `start` - beginning of the region.
+/// * `end` - end of the region.
+/// * `output` - path to the file to write. Existing file is overwritten.
+///
+/// # Errors
+///
+/// Returns an error message if the output file format is not supported, the output file is the
+/// input one, the input file can't be loaded, the region is not within the audio track, or the
+/// output file can't be written. The message is in English, unless another language is set with
+/// [i18n::set_lang].
+pub fn cut_file(
+    input: &std::path::Path,
+    start: std::time::Duration,
+    end: std::time::Duration,
+    output: &std::path::Path,
+) -> Result<(), String> {
+    let format = output
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(export::ExportFormat::from_extension)
+        .ok_or_else(|| {
+            i18n::tr_args(
+                i18n::Key::UnsupportedFile,
+                &[
+                    &output.to_string_lossy(),
+                    &export::ExportFormat::EXTENSIONS.join(", "),
+                ],
+            )
+        })?;
+    if start >= end {
+        return Err(String::from(i18n::tr(i18n::Key::RegionStartAfterEnd)));
+    }
+    export::check_destination(input, output)?;
+
+    let pipeline = pipeline::AudioPipeline::load(input)?;
+    if end > pipeline.duration() {
+        return Err(i18n::tr_args(
+            i18n::Key::RegionPastTrackEnd,
+            &[&format!("{:.3}", pipeline.duration().as_secs_f64())],
+        ));
+    }
+    let options = pipeline::ExportOptions {
+        fades: pipeline::Fades::default(),
+        normalize: false,
+        gain_db: 0.0,
+        limit: false,
+        channel_mode: export::ChannelMode::Keep,
+        format,
+    };
+    pipeline.export_region(
+        start,
+        end,
+        options,
+        output,
+        &export::ExportProgress::default(),
+    )
+}
 
 /// Audio source: decoded audio data.
 ///
@@ -16,3 +104,432 @@ type DecodedAudioSource = rodio::Decoder<std::io::BufReader<std::fs::File>>;
 /// audio source data and can be cloned, so we decode audio data only once, even if playing it
 /// multiple times.
 type AudioSourceBuf = rodio::source::Buffered<DecodedAudioSource>;
+
+/// Audio source, that decodes the audio file again for each copy, instead of keeping decoded audio
+/// data in memory, like [AudioSourceBuf] does.
+///
+/// It trades CPU time for memory, so long lossless audio tracks, that would exhaust memory once
+/// decoded, can be played and processed too. A copy continues from the same position, as the
+/// copied audio source, seeking the decoder there, if it supports seeking.
+pub(crate) struct StreamedSource {
+    /// Path to the audio file.
+    path: std::sync::Arc<std::path::Path>,
+    /// Number of channels of the audio track.
+    channels: rodio::ChannelCount,
+    /// Number of frames per second of the audio track.
+    sample_rate: rodio::SampleRate,
+    /// Index of the next sample of the audio track.
+    position: u64,
+    /// Whether the audio file is opened for decoding. It is opened, once the first sample is
+    /// requested, so copies, that are never played, don't open it.
+    opened: bool,
+    /// Decoder of the audio file, if it is opened successfully. It is boxed, as it is much bigger,
+    /// than the rest of the audio source.
+    decoder: Option<Box<DecodedAudioSource>>,
+}
+
+impl StreamedSource {
+    /// Creates a new [StreamedSource] of the audio file, starting from its beginning.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - path to the audio file, that has been decoded already, so it is known to be
+    ///   decodable.
+    /// * `channels` - number of channels of the audio track.
+    /// * `sample_rate` - number of frames per second of the audio track.
+    pub(crate) fn new(
+        path: &std::path::Path,
+        channels: rodio::ChannelCount,
+        sample_rate: rodio::SampleRate,
+    ) -> Self {
+        Self {
+            path: std::sync::Arc::from(path),
+            channels,
+            sample_rate,
+            position: 0,
+            opened: false,
+            decoder: None,
+        }
+    }
+
+    /// Returns a copy of the audio source, that starts the given number of samples later.
+    ///
+    /// # Parameters
+    ///
+    /// * `samples` - number of samples to skip.
+    pub(crate) fn skipped(&self, samples: u64) -> Self {
+        let mut source = self.clone();
+        source.position += samples;
+        source
+    }
+
+    /// Opens the audio file for decoding from the current position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if the file can't be opened or decoded anymore, e.g. it is removed
+    /// since it was loaded.
+    fn open(&self) -> Result<DecodedAudioSource, String> {
+        let file = std::fs::File::open(&self.path).map_err(|error| error.to_string())?;
+        let mut decoder = rodio::Decoder::new(std::io::BufReader::new(file))
+            .map_err(|error| error.to_string())?;
+
+        let channels = u64::from(self.channels.max(1));
+        let frame = self.position / channels;
+        // Decoding everything up to the position takes a while, so it is only done, when the
+        // decoder can't seek
+        let skipped = match decoder.try_seek(pipeline::frame_position(frame, self.sample_rate)) {
+            Ok(()) => self.position % channels,
+            Err(_) => self.position,
+        };
+        decoder.by_ref().take(skipped as usize).for_each(drop);
+        Ok(decoder)
+    }
+}
+
+impl Clone for StreamedSource {
+    fn clone(&self) -> Self {
+        Self {
+            path: std::sync::Arc::clone(&self.path),
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            position: self.position,
+            opened: false,
+            decoder: None,
+        }
+    }
+}
+
+impl Iterator for StreamedSource {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<rodio::Sample> {
+        if !self.opened {
+            self.opened = true;
+            self.decoder = match self.open() {
+                Ok(decoder) => Some(Box::new(decoder)),
+                Err(error) => {
+                    log::warn!("Failed to decode {}: {}", self.path.display(), error);
+                    None
+                }
+            };
+        }
+
+        let sample = self.decoder.as_mut()?.next()?;
+        self.position += 1;
+        Some(sample)
+    }
+}
+
+impl rodio::Source for StreamedSource {
+    fn current_span_len(&self) -> Option<usize> {
+        self.decoder.as_ref()?.current_span_len()
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.decoder
+            .as_ref()
+            .map_or(self.channels, |decoder| decoder.channels())
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.decoder
+            .as_ref()
+            .map_or(self.sample_rate, |decoder| decoder.sample_rate())
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        self.decoder.as_ref()?.total_duration()
+    }
+
+    fn try_seek(&mut self, _: std::time::Duration) -> Result<(), rodio::source::SeekError> {
+        // Wrapping audio sources, like the one taking a region, would keep counting from the
+        // position before seeking, so seeking is refused like by the buffered audio source, and
+        // a copy is rather started at the position, see [PlayedSource::starting_at]
+        Err(rodio::source::SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+/// Audio source of the whole audio track, that is copied to play or process the audio track any
+/// number of times.
+#[derive(Clone)]
+pub(crate) enum TrackSource {
+    /// The audio track, decoded once and kept in memory.
+    Buffered(AudioSourceBuf),
+    /// The audio track, decoded from the audio file again for each copy.
+    Streamed(StreamedSource),
+}
+
+// Streamed audio track would be decoded up to the skipped position, if it was skipped like the
+// buffered one, so the decoder is rather sought there, once the audio file is opened
+impl TrackSource {
+    /// Returns a copy of the audio source, that starts from the given position.
+    ///
+    /// # Parameters
+    ///
+    /// * `position` - duration of the part of the audio track to skip.
+    pub(crate) fn starting_at(
+        &self,
+        position: std::time::Duration,
+    ) -> rodio::source::SkipDuration<Self> {
+        match self {
+            TrackSource::Buffered(source) => {
+                TrackSource::Buffered(source.clone()).skip_duration(position)
+            }
+            TrackSource::Streamed(source) => {
+                let frame = pipeline::frame_index(position, source.sample_rate);
+                let samples = frame * u64::from(source.channels);
+                TrackSource::Streamed(source.skipped(samples))
+                    .skip_duration(std::time::Duration::ZERO)
+            }
+        }
+    }
+}
+
+impl Iterator for TrackSource {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<rodio::Sample> {
+        match self {
+            TrackSource::Buffered(source) => source.next(),
+            TrackSource::Streamed(source) => source.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            TrackSource::Buffered(source) => source.size_hint(),
+            TrackSource::Streamed(source) => source.size_hint(),
+        }
+    }
+}
+
+impl rodio::Source for TrackSource {
+    fn current_span_len(&self) -> Option<usize> {
+        match self {
+            TrackSource::Buffered(source) => source.current_span_len(),
+            TrackSource::Streamed(source) => source.current_span_len(),
+        }
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        match self {
+            TrackSource::Buffered(source) => source.channels(),
+            TrackSource::Streamed(source) => source.channels(),
+        }
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        match self {
+            TrackSource::Buffered(source) => source.sample_rate(),
+            TrackSource::Streamed(source) => source.sample_rate(),
+        }
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        match self {
+            TrackSource::Buffered(source) => source.total_duration(),
+            TrackSource::Streamed(source) => source.total_duration(),
+        }
+    }
+
+    fn try_seek(&mut self, position: std::time::Duration) -> Result<(), rodio::source::SeekError> {
+        match self {
+            TrackSource::Buffered(source) => source.try_seek(position),
+            TrackSource::Streamed(source) => source.try_seek(position),
+        }
+    }
+}
+
+/// Audio source, sent to the audio playback thread for playing.
+#[derive(Clone)]
+pub(crate) enum PlayedSource {
+    /// A part of [TrackSource], bounded by the skipped beginning and the taken duration, so
+    /// either a tail of the audio track or a region of it can be played the same way.
+    Original {
+        /// The whole audio track, kept to start the part again from another position.
+        track: TrackSource,
+        /// Duration of the skipped beginning of the audio track.
+        skipped: std::time::Duration,
+        /// Duration of the part.
+        taken: std::time::Duration,
+        /// The part of the audio track.
+        source: rodio::source::TakeDuration<rodio::source::SkipDuration<TrackSource>>,
+    },
+    /// A region of the audio track, processed the same way, as it is exported, so user can
+    /// preview the result.
+    Processed(rodio::buffer::SamplesBuffer),
+}
+
+impl PlayedSource {
+    /// Creates a new [PlayedSource::Original] of the part of the audio track.
+    ///
+    /// # Parameters
+    ///
+    /// * `track` - the whole audio track.
+    /// * `skipped` - duration of the part of the audio track to skip.
+    /// * `taken` - duration of the part of the audio track to play after the skipped one.
+    pub(crate) fn original(
+        track: &TrackSource,
+        skipped: std::time::Duration,
+        taken: std::time::Duration,
+    ) -> Self {
+        PlayedSource::Original {
+            track: track.clone(),
+            skipped,
+            taken,
+            source: track.starting_at(skipped).take_duration(taken),
+        }
+    }
+
+    /// Returns a copy of the audio source, that starts from the given position.
+    ///
+    /// Unlike skipping the beginning of a copy, it doesn't decode the skipped part of a streamed
+    /// audio track.
+    ///
+    /// # Parameters
+    ///
+    /// * `position` - position, counted from the beginning of the audio source.
+    pub(crate) fn starting_at(&self, position: std::time::Duration) -> Self {
+        match self {
+            PlayedSource::Original {
+                track,
+                skipped,
+                taken,
+                ..
+            } => PlayedSource::original(track, *skipped + position, taken.saturating_sub(position)),
+            PlayedSource::Processed(source) => {
+                let mut source = source.clone();
+                // Samples buffer seeks right to the sample in memory, so it never fails
+                let _ = source.try_seek(position);
+                PlayedSource::Processed(source)
+            }
+        }
+    }
+}
+
+impl Iterator for PlayedSource {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<rodio::Sample> {
+        match self {
+            PlayedSource::Original { source, .. } => source.next(),
+            PlayedSource::Processed(source) => source.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            PlayedSource::Original { source, .. } => source.size_hint(),
+            PlayedSource::Processed(source) => source.size_hint(),
+        }
+    }
+}
+
+impl rodio::Source for PlayedSource {
+    fn current_span_len(&self) -> Option<usize> {
+        match self {
+            PlayedSource::Original { source, .. } => source.current_span_len(),
+            PlayedSource::Processed(source) => source.current_span_len(),
+        }
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        match self {
+            PlayedSource::Original { source, .. } => source.channels(),
+            PlayedSource::Processed(source) => source.channels(),
+        }
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        match self {
+            PlayedSource::Original { source, .. } => source.sample_rate(),
+            PlayedSource::Processed(source) => source.sample_rate(),
+        }
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        match self {
+            PlayedSource::Original { source, .. } => source.total_duration(),
+            PlayedSource::Processed(source) => source.total_duration(),
+        }
+    }
+
+    fn try_seek(&mut self, position: std::time::Duration) -> Result<(), rodio::source::SeekError> {
+        match self {
+            PlayedSource::Original { source, .. } => source.try_seek(position),
+            PlayedSource::Processed(source) => source.try_seek(position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Returns path to a file in the temporary directory, unique for this test run.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - name of the file, unique among the tests.
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("audio_cutter_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn cut_file_writes_region() {
+        let output = temp_file("cut.wav");
+
+        cut_file(
+            std::path::Path::new(TEST_TRACK),
+            Duration::from_millis(250),
+            Duration::from_millis(750),
+            &output,
+        )
+        .unwrap();
+
+        let region = pipeline::AudioPipeline::load(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert_eq!(region.duration(), Duration::from_millis(500));
+        assert_eq!(region.sample_rate(), 8000);
+        assert_eq!(region.channels(), 2);
+    }
+
+    #[test]
+    fn cut_file_rejects_start_at_or_after_end() {
+        let output = temp_file("empty_cut.wav");
+
+        for (start, end) in [(500, 500), (750, 250)] {
+            let result = cut_file(
+                std::path::Path::new(TEST_TRACK),
+                Duration::from_millis(start),
+                Duration::from_millis(end),
+                &output,
+            );
+
+            assert_eq!(
+                result,
+                Err(String::from("Region start must be before its end"))
+            );
+        }
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn cut_file_fails_on_missing_input() {
+        let output = temp_file("missing_cut.wav");
+
+        let result = cut_file(
+            &temp_file("missing.wav"),
+            Duration::ZERO,
+            Duration::from_millis(500),
+            &output,
+        );
+
+        assert!(result.is_err());
+        assert!(!output.exists());
+    }
+}
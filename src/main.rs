@@ -3,6 +3,12 @@ use eframe::egui;
 use std::sync::Arc;
 
 fn main() -> eframe::Result {
+    // Only errors are logged by default, and more is enabled with RUST_LOG, e.g. RUST_LOG=debug
+    env_logger::init();
+
+    // File managers pass the file to open as the first argument, e.g. for "Open with"
+    let file = std::env::args_os().nth(1).map(std::path::PathBuf::from);
+
     let icon = eframe::icon_data::from_png_bytes(include_bytes!("../icon1100.png"))
         .expect("Application icon must be valid .png");
 
@@ -12,12 +18,14 @@ fn main() -> eframe::Result {
             .with_min_inner_size([600.0, 300.0])
             .with_inner_size([600.0, 300.0])
             .with_icon(Arc::new(icon)),
+        // Restore window size and position, left by the previous run, over the default ones
+        persist_window: true,
         ..Default::default()
     };
 
     eframe::run_native(
         "Audio Cutter",
         window_options,
-        Box::new(|cc| Ok(Box::<audio_cutter_app::AudioCutterApp>::default())),
+        Box::new(|cc| Ok(Box::new(audio_cutter_app::AudioCutterApp::new(cc, file)))),
     )
 }
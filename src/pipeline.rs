@@ -0,0 +1,1066 @@
+use crate::export::{ChannelMode, ExportFormat, ExportProgress};
+use crate::i18n::{tr_args, Key};
+use crate::spectrogram::Spectrogram;
+use crate::waveform::Waveform;
+use rodio::Source;
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+
+/// Maximum number of samples of an audio track, that are cached as a plain vector after load.
+///
+/// It is 128 MiB of `f32` samples, e.g. about 11 minutes of 48 kHz stereo audio track.
+const CACHED_SAMPLES_LIMIT: usize = 32 * 1024 * 1024;
+
+/// Size of an audio file in bytes, above which it is streamed from disk with [LoadMode::Auto].
+///
+/// Decoded audio track takes several times more memory, than its file, e.g. 256 MiB of WAV file
+/// is 512 MiB of `f32` samples, and MP3 file is decoded into ten times more.
+pub const STREAMING_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// How the decoded audio track is kept for playing and processing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum LoadMode {
+    /// Streamed from disk, if the audio file is bigger than [STREAMING_FILE_SIZE], and kept in
+    /// memory otherwise.
+    #[default]
+    Auto,
+    /// Decoded once and kept in memory, so it is played and processed without decoding again.
+    Memory,
+    /// Decoded from disk again each time it is played or processed, so it takes little memory,
+    /// but more CPU time.
+    Streamed,
+}
+
+/// Audio processing pipeline of a single audio track.
+///
+/// Owns the decoded audio track and computes everything, that is derived from it, independently
+/// of the UI. So the same processing is used by the UI and can be driven without spinning up a
+/// window.
+///
+/// Cloning is cheap, as the decoded audio track is shared between the clones, so a clone can be
+/// handed over to a background thread.
+#[derive(Clone)]
+pub struct AudioPipeline {
+    /// Audio source of the audio track.
+    source: crate::TrackSource,
+    /// Number of channels of the audio track.
+    channels: u16,
+    /// Number of samples per second of the audio track.
+    sample_rate: u32,
+    /// Total duration of the audio track.
+    duration: Duration,
+    /// Average bitrate of the audio file in kilobits per second, if it is known.
+    bitrate: Option<u32>,
+    /// Last modification time of the audio file at load, if it is known.
+    modified: Option<std::time::SystemTime>,
+    /// Interleaved samples of all channels of the audio track, decoded once at load, if there are
+    /// at most [CACHED_SAMPLES_LIMIT] of them, and the audio track is not streamed.
+    ///
+    /// Buffered audio source keeps decoded samples too, but walks them sample by sample through
+    /// shared frames, so exports and waveform computations are much faster over a plain vector.
+    /// The price is a second copy of the samples in memory, so longer audio tracks are read from
+    /// the audio source only. They are shared with background computations.
+    samples: Option<std::sync::Arc<Vec<f32>>>,
+}
+
+impl AudioPipeline {
+    /// Loads the audio track from the given file.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - path to the audio file to load.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if the file can't be opened or decoded, e.g. it is corrupt or
+    /// there is no decoder for the audio track codec, like for an OGG file with Opus instead of
+    /// Vorbis.
+    ///
+    /// Also returns an error message if the sample rate of the audio track changes mid-stream, as
+    /// all positions and durations are computed using the single sample rate, stored at load.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        Self::load_with_mode(path, LoadMode::Auto)
+    }
+
+    /// Loads the audio track from the given file, keeping it as chosen.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - path to the audio file to load.
+    /// * `mode` - how to keep the decoded audio track.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message in the same cases as [Self::load].
+    pub fn load_with_mode(path: &Path, mode: LoadMode) -> Result<Self, String> {
+        log::debug!("Loading audio source: {}...", path.display());
+        // The file could be removed or renamed since it was chosen
+        let file = File::open(path).map_err(|error| tr_args(Key::FileOpenFailed, &[&error]))?;
+        let metadata = file.metadata().ok();
+        let file_size = metadata.as_ref().map(|metadata| metadata.len());
+        let modified = metadata.and_then(|metadata| metadata.modified().ok());
+
+        let decoder = match rodio::Decoder::new(std::io::BufReader::new(file)) {
+            Ok(decoder) => decoder,
+            Err(rodio::decoder::DecoderError::UnrecognizedFormat) => {
+                // OGG container is decoded, but only Vorbis codec inside it is supported, so
+                // Opus is the likely reason for OGG files. Other files are likely not audio ones
+                let ogg = path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| {
+                        extension.eq_ignore_ascii_case("ogg")
+                            || extension.eq_ignore_ascii_case("opus")
+                    });
+                let key = if ogg {
+                    Key::UnsupportedCodec
+                } else {
+                    Key::UnrecognizedFormat
+                };
+                return Err(tr_args(key, &[&crate::SUPPORTED_EXTENSIONS.join(", ")]));
+            }
+            Err(error) => return Err(tr_args(Key::DecodeFailed, &[&error])),
+        };
+        let streamed = match mode {
+            LoadMode::Auto => file_size.is_some_and(|size| size > STREAMING_FILE_SIZE),
+            LoadMode::Memory => false,
+            LoadMode::Streamed => true,
+        };
+        let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+        let source = if streamed {
+            crate::TrackSource::Streamed(crate::StreamedSource::new(path, channels, sample_rate))
+        } else {
+            crate::TrackSource::Buffered(decoder.buffered())
+        };
+        let mut pipeline = Self {
+            channels,
+            sample_rate,
+            source,
+            duration: Duration::ZERO,
+            bitrate: None,
+            modified,
+            samples: None,
+        };
+        log::debug!("Samples rate: {}", pipeline.sample_rate);
+        log::debug!("Channels: {}", pipeline.channels);
+        log::debug!("Streamed: {}", streamed);
+
+        pipeline.duration = pipeline.decode()?;
+        pipeline.bitrate = file_size.and_then(|size| average_bitrate(size, pipeline.duration));
+        Ok(pipeline)
+    }
+
+    /// Returns the audio source of the whole audio track.
+    pub(crate) fn source(&self) -> &crate::TrackSource {
+        &self.source
+    }
+
+    /// Returns number of channels of the audio track.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Returns number of samples per second of the audio track.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns total duration of the audio track.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns average bitrate of the audio file in kilobits per second, if it is known.
+    pub fn bitrate(&self) -> Option<u32> {
+        self.bitrate
+    }
+
+    /// Returns whether the audio track is decoded from disk again each time it is played or
+    /// processed, instead of being kept in memory.
+    pub fn streamed(&self) -> bool {
+        matches!(self.source, crate::TrackSource::Streamed(_))
+    }
+
+    /// Returns last modification time of the audio file at load, if it is known.
+    pub fn modified(&self) -> Option<std::time::SystemTime> {
+        self.modified
+    }
+
+    /// Decodes the whole audio track, caching its samples if there are not too many of them, and
+    /// measures its duration, checking that its sample rate stays the same.
+    ///
+    /// If the audio source is buffered, this decodes the whole audio track once, so later passes
+    /// over the audio source are cheap. Streamed audio source is decoded again on each pass, and
+    /// its samples are only counted, not cached, so it takes little memory.
+    fn decode(&mut self) -> Result<Duration, String> {
+        let (samples_count, samples) =
+            decode_samples(self.source.clone(), self.sample_rate, !self.streamed())?;
+        self.samples = samples.map(std::sync::Arc::new);
+
+        let frames_count = samples_count / u64::from(self.channels);
+        Ok(Duration::from_nanos(
+            (u128::from(frames_count) * 1_000_000_000 / u128::from(self.sample_rate)) as u64,
+        ))
+    }
+
+    /// Writes the given region of the audio track to a new audio file, keeping the sample rate and
+    /// channels of the audio track.
+    ///
+    /// # Parameters
+    ///
+    /// * `start` - beginning of the region.
+    /// * `end` - end of the region.
+    /// * `options` - processing of the region and format of the file to write.
+    /// * `path` - path to the file to write.
+    /// * `progress` - where to report progress of the writing to.
+    pub fn export_region(
+        &self,
+        start: Duration,
+        end: Duration,
+        options: ExportOptions,
+        path: &Path,
+        progress: &ExportProgress,
+    ) -> Result<(), String> {
+        log::debug!(
+            "Exporting region {:?}..{:?} to {}...",
+            start,
+            end,
+            path.display()
+        );
+        let samples = crate::export::downmix(
+            self.region_samples(start, end, options.fades),
+            self.channels,
+            options.channel_mode,
+        );
+        let channels = options.channel_mode.channels(self.channels);
+        let frames_count = frame_index(end, self.sample_rate)
+            .saturating_sub(frame_index(start, self.sample_rate))
+            as usize;
+        let samples_count = frames_count * usize::from(channels);
+        self.write_processed(path, samples, samples_count, channels, options, progress)
+    }
+
+    /// Writes the audio track without the given region to a new audio file, joining the parts
+    /// before and after the region with a crossfade, like [crate::export::remove_range] does.
+    ///
+    /// The parts are written as they are decoded, and only the frames, that overlap at the join,
+    /// are kept in memory, so streamed audio tracks are not decoded into memory as a whole.
+    ///
+    /// Fade-in of the export options applies to the beginning of the audio track, and fade-out
+    /// to its end.
+    ///
+    /// # Parameters
+    ///
+    /// * `start` - beginning of the removed region.
+    /// * `end` - end of the removed region.
+    /// * `crossfade` - duration of the crossfade at the join.
+    /// * `options` - processing of the rest of the audio track and format of the file to write.
+    /// * `path` - path to the file to write.
+    /// * `progress` - where to report progress of the writing to.
+    pub fn export_cut_out(
+        &self,
+        start: Duration,
+        end: Duration,
+        crossfade: Duration,
+        options: ExportOptions,
+        path: &Path,
+        progress: &ExportProgress,
+    ) -> Result<(), String> {
+        log::debug!(
+            "Exporting the audio track without {:?}..{:?} to {}...",
+            start,
+            end,
+            path.display()
+        );
+        let total_frames = frame_index(self.duration, self.sample_rate);
+        let end_frame = frame_index(end, self.sample_rate).min(total_frames);
+        let start_frame = frame_index(start, self.sample_rate).min(end_frame);
+        // The overlap is clamped to the parts the same way, as crossfade of clips does
+        let overlap = frame_index(crossfade, self.sample_rate)
+            .min(start_frame)
+            .min(total_frames - end_frame);
+        let channels = options.channel_mode.channels(self.channels);
+
+        let before = crate::export::downmix(
+            self.frames_samples(
+                0..start_frame - overlap,
+                Fades {
+                    fade_in: options.fades.fade_in,
+                    fade_out: Duration::ZERO,
+                },
+            ),
+            self.channels,
+            options.channel_mode,
+        );
+        let join = crate::export::crossfade(
+            vec![
+                crate::export::downmix(
+                    self.frames_samples(start_frame - overlap..start_frame, Fades::default()),
+                    self.channels,
+                    options.channel_mode,
+                )
+                .collect(),
+                crate::export::downmix(
+                    self.frames_samples(end_frame..end_frame + overlap, Fades::default()),
+                    self.channels,
+                    options.channel_mode,
+                )
+                .collect(),
+            ],
+            channels,
+            overlap as usize,
+        );
+        let after = crate::export::downmix(
+            self.frames_samples(
+                end_frame + overlap..total_frames,
+                Fades {
+                    fade_in: Duration::ZERO,
+                    fade_out: options.fades.fade_out,
+                },
+            ),
+            self.channels,
+            options.channel_mode,
+        );
+
+        let frames_count = start_frame + (total_frames - end_frame) - overlap;
+        self.write_processed(
+            path,
+            before.chain(join).chain(after),
+            frames_count as usize * usize::from(channels),
+            channels,
+            options,
+            progress,
+        )
+    }
+
+    /// Writes the given regions of the audio track one after another to a single new audio file,
+    /// with the given transition between the adjacent ones.
+    ///
+    /// Fade-in of the export options applies to the beginning of the first region, and fade-out
+    /// to the end of the last one.
+    ///
+    /// # Parameters
+    ///
+    /// * `regions` - beginnings and ends of the regions in the order of joining.
+    /// * `transition` - transition between the adjacent regions.
+    /// * `options` - processing of the joined regions and format of the file to write.
+    /// * `path` - path to the file to write.
+    /// * `progress` - where to report progress of the writing to.
+    pub fn export_joined(
+        &self,
+        regions: &[(Duration, Duration)],
+        transition: Transition,
+        options: ExportOptions,
+        path: &Path,
+        progress: &ExportProgress,
+    ) -> Result<(), String> {
+        log::debug!(
+            "Exporting {} joined regions to {}...",
+            regions.len(),
+            path.display()
+        );
+        let clips = regions
+            .iter()
+            .enumerate()
+            .map(|(index, &(start, end))| {
+                let fades = Fades {
+                    fade_in: if index == 0 {
+                        options.fades.fade_in
+                    } else {
+                        Duration::ZERO
+                    },
+                    fade_out: if index + 1 == regions.len() {
+                        options.fades.fade_out
+                    } else {
+                        Duration::ZERO
+                    },
+                };
+                crate::export::downmix(
+                    self.region_samples(start, end, fades),
+                    self.channels,
+                    options.channel_mode,
+                )
+                .collect()
+            })
+            .collect();
+        let channels = options.channel_mode.channels(self.channels);
+        let samples = match transition {
+            Transition::Crossfade(duration) => crate::export::crossfade(
+                clips,
+                channels,
+                frame_index(duration, self.sample_rate) as usize,
+            ),
+            Transition::Silence(duration) => crate::export::insert_silence(
+                clips,
+                channels,
+                frame_index(duration, self.sample_rate) as usize,
+            ),
+        };
+        self.write_processed(
+            path,
+            samples.iter().copied(),
+            samples.len(),
+            channels,
+            options,
+            progress,
+        )
+    }
+
+    /// Writes the samples to a new audio file, normalizing, amplifying and limiting them as chosen.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - path to the file to write.
+    /// * `samples` - interleaved samples of all channels, mixed down as chosen.
+    /// * `samples_count` - number of the samples.
+    /// * `channels` - number of channels of the samples.
+    /// * `options` - processing of the samples and format of the file to write.
+    /// * `progress` - where to report progress of the writing to.
+    fn write_processed(
+        &self,
+        path: &Path,
+        samples: impl Iterator<Item = f32> + Clone,
+        samples_count: usize,
+        channels: u16,
+        options: ExportOptions,
+        progress: &ExportProgress,
+    ) -> Result<(), String> {
+        // Gain is applied after normalization, so it moves the peaks away from the normalized
+        // level by the chosen amount
+        if options.normalize {
+            crate::export::write(
+                path,
+                crate::export::track_progress(
+                    crate::export::apply_gain(
+                        crate::export::normalize(samples, crate::export::NORMALIZE_TARGET_DBFS),
+                        options.gain_db,
+                        options.limit,
+                    ),
+                    samples_count,
+                    progress,
+                ),
+                self.sample_rate,
+                channels,
+                options.format,
+            )
+        } else {
+            crate::export::write(
+                path,
+                crate::export::track_progress(
+                    crate::export::apply_gain(samples, options.gain_db, options.limit),
+                    samples_count,
+                    progress,
+                ),
+                self.sample_rate,
+                channels,
+                options.format,
+            )
+        }
+    }
+
+    /// Returns the given region of the audio track, processed the same way, as it is exported,
+    /// ready for playing.
+    ///
+    /// # Parameters
+    ///
+    /// * `start` - beginning of the region.
+    /// * `end` - end of the region.
+    /// * `options` - processing of the region. The format is ignored.
+    pub(crate) fn processed_region(
+        &self,
+        start: Duration,
+        end: Duration,
+        options: ExportOptions,
+    ) -> rodio::buffer::SamplesBuffer {
+        let samples = crate::export::downmix(
+            self.region_samples(start, end, options.fades),
+            self.channels,
+            options.channel_mode,
+        );
+        let samples: Vec<f32> = if options.normalize {
+            crate::export::apply_gain(
+                crate::export::normalize(samples, crate::export::NORMALIZE_TARGET_DBFS),
+                options.gain_db,
+                options.limit,
+            )
+            .collect()
+        } else {
+            crate::export::apply_gain(samples, options.gain_db, options.limit).collect()
+        };
+        rodio::buffer::SamplesBuffer::new(
+            options.channel_mode.channels(self.channels),
+            self.sample_rate,
+            samples,
+        )
+    }
+
+    /// Returns interleaved samples of all channels of the given region of the audio track.
+    ///
+    /// # Parameters
+    ///
+    /// * `start` - beginning of the region.
+    /// * `end` - end of the region.
+    /// * `fades` - fade-in and fade-out to apply to the region.
+    fn region_samples(
+        &self,
+        start: Duration,
+        end: Duration,
+        fades: Fades,
+    ) -> impl Iterator<Item = f32> + Clone + '_ {
+        // Count whole frames, so the region never starts or ends in the middle of a frame, which
+        // would swap channels
+        self.frames_samples(
+            frame_index(start, self.sample_rate)..frame_index(end, self.sample_rate),
+            fades,
+        )
+    }
+
+    /// Returns interleaved samples of all channels of the given frames of the audio track.
+    ///
+    /// # Parameters
+    ///
+    /// * `frames` - range of the frames.
+    /// * `fades` - fade-in and fade-out to apply to the frames.
+    fn frames_samples(
+        &self,
+        frames: std::ops::Range<u64>,
+        fades: Fades,
+    ) -> impl Iterator<Item = f32> + Clone + '_ {
+        let channels = u64::from(self.channels);
+        let start_frame = frames.start;
+        let frames_count = frames.end.saturating_sub(start_frame);
+        let fade_in_frames = frame_index(fades.fade_in, self.sample_rate);
+        let fade_out_frames = frame_index(fades.fade_out, self.sample_rate);
+
+        let skipped = (start_frame * channels) as usize;
+        let taken = (frames_count * channels) as usize;
+        let samples = match &self.samples {
+            Some(samples) => {
+                let start = skipped.min(samples.len());
+                let end = (skipped + taken).min(samples.len());
+                RegionSamples::Cached(samples[start..end].iter().copied())
+            }
+            None => match &self.source {
+                crate::TrackSource::Buffered(source) => {
+                    RegionSamples::Buffered(source.clone().skip(skipped).take(taken))
+                }
+                crate::TrackSource::Streamed(source) => {
+                    RegionSamples::Streamed(source.skipped(skipped as u64).take(taken))
+                }
+            },
+        };
+
+        samples.enumerate().map(move |(index, sample)| {
+            let frame = index as u64 / channels;
+            // Linear gain ramps from silence at the region bounds to the full volume
+            let fade_in_gain = if frame < fade_in_frames {
+                frame as f32 / fade_in_frames as f32
+            } else {
+                1.0
+            };
+            let fade_out_gain = if frames_count - frame <= fade_out_frames {
+                (frames_count - frame - 1) as f32 / fade_out_frames as f32
+            } else {
+                1.0
+            };
+            sample * fade_in_gain * fade_out_gain
+        })
+    }
+
+    /// Computes the sound wave of the audio track.
+    pub fn compute_waveform(&self) -> Waveform {
+        let frames_count = frame_index(self.duration, self.sample_rate);
+        match &self.samples {
+            Some(samples) => Waveform::compute(
+                samples.iter().copied(),
+                self.channels,
+                self.sample_rate,
+                frames_count,
+            ),
+            None => Waveform::compute(
+                self.source.clone(),
+                self.channels,
+                self.sample_rate,
+                frames_count,
+            ),
+        }
+    }
+
+    /// Finds the sounding segments of the audio track, separated by silences. See
+    /// [crate::analysis::find_sound_segments].
+    ///
+    /// # Parameters
+    ///
+    /// * `threshold` - amplitude, below which samples are silent, where `1.0` is the full scale.
+    /// * `min_silence` - minimal duration of a silence, that separates the segments.
+    pub fn find_sound_segments(
+        &self,
+        threshold: f32,
+        min_silence: Duration,
+    ) -> Vec<(Duration, Duration)> {
+        match &self.samples {
+            Some(samples) => crate::analysis::find_sound_segments(
+                samples.iter().copied(),
+                self.channels,
+                self.sample_rate,
+                threshold,
+                min_silence,
+            ),
+            None => crate::analysis::find_sound_segments(
+                self.source.clone(),
+                self.channels,
+                self.sample_rate,
+                threshold,
+                min_silence,
+            ),
+        }
+    }
+
+    /// Starts computing the spectrogram of the audio track in a background thread, as it takes a
+    /// while on long audio tracks.
+    ///
+    /// Returns a receiver, that gets the spectrogram once it is computed. If the receiver is
+    /// dropped before that, the spectrogram is thrown away.
+    pub fn compute_spectrogram_in_background(&self) -> std::sync::mpsc::Receiver<Spectrogram> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let frames_count = frame_index(self.duration, self.sample_rate);
+        let (channels, sample_rate) = (self.channels, self.sample_rate);
+        let samples = self.samples.clone();
+        let source = self.source.clone();
+
+        std::thread::spawn(move || {
+            let spectrogram = match samples {
+                Some(samples) => Spectrogram::compute(
+                    samples.iter().copied(),
+                    channels,
+                    sample_rate,
+                    frames_count,
+                ),
+                None => Spectrogram::compute(source, channels, sample_rate, frames_count),
+            };
+            // Nobody waits for the spectrogram anymore, if the receiver is gone
+            let _ = sender.send(spectrogram);
+        });
+        receiver
+    }
+}
+
+/// Samples of a region of the audio track, taken from the cached samples, if there are ones, or
+/// from the audio source otherwise.
+#[derive(Clone)]
+enum RegionSamples<'a> {
+    Cached(std::iter::Copied<std::slice::Iter<'a, f32>>),
+    Buffered(std::iter::Take<std::iter::Skip<crate::AudioSourceBuf>>),
+    /// Streamed audio source starts at the skipped sample on its own, so the skipped samples are
+    /// not decoded.
+    Streamed(std::iter::Take<crate::StreamedSource>),
+}
+
+impl Iterator for RegionSamples<'_> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        match self {
+            RegionSamples::Cached(samples) => samples.next(),
+            RegionSamples::Buffered(samples) => samples.next(),
+            RegionSamples::Streamed(samples) => samples.next(),
+        }
+    }
+}
+
+/// Fade-in and fade-out, applied to the bounds of an exported region to avoid clicks, when it
+/// starts or ends in the middle of a sound wave.
+#[derive(Clone, Copy, Default)]
+pub struct Fades {
+    /// Duration of the volume rise from silence at the beginning of the region. Zero means no
+    /// fade-in.
+    pub fade_in: Duration,
+    /// Duration of the volume fall to silence at the end of the region. Zero means no fade-out.
+    pub fade_out: Duration,
+}
+
+/// Transition between the adjacent regions, joined into a single file.
+#[derive(Clone, Copy)]
+pub enum Transition {
+    /// The regions overlap by the duration, so the former one fades out, while the latter one
+    /// fades in. Zero duration joins the regions back to back.
+    Crossfade(Duration),
+    /// The regions are separated by silence of the duration.
+    Silence(Duration),
+}
+
+/// Processing of an exported region and format of the file, it is written to.
+#[derive(Clone, Copy)]
+pub struct ExportOptions {
+    /// Fade-in and fade-out to apply to the region.
+    pub fades: Fades,
+    /// Whether to scale the region, so its peak hits [crate::export::NORMALIZE_TARGET_DBFS].
+    pub normalize: bool,
+    /// Gain in decibels to amplify or attenuate the region by, after normalization, if any.
+    pub gain_db: f32,
+    /// Whether to hard-limit the amplified samples, see [crate::export::apply_gain].
+    pub limit: bool,
+    /// Channels of the written file.
+    pub channel_mode: ChannelMode,
+    /// Format of the written file.
+    pub format: ExportFormat,
+}
+
+/// Decodes all samples of the audio source, checking that its sample rate stays the same.
+///
+/// Returns number of the samples, along with the samples themselves, if they are cached and there
+/// are at most [CACHED_SAMPLES_LIMIT] of them.
+///
+/// # Parameters
+///
+/// * `source` - the audio source to decode.
+/// * `sample_rate` - sample rate of the audio source at its beginning.
+/// * `cache` - whether to keep the samples, or only count them.
+///
+/// # Errors
+///
+/// Returns an error message if the sample rate of the audio source changes mid-stream.
+fn decode_samples(
+    mut source: impl Source,
+    sample_rate: u32,
+    cache: bool,
+) -> Result<(u64, Option<Vec<f32>>), String> {
+    let mut samples_count: u64 = 0;
+    let mut samples = cache.then(Vec::new);
+    loop {
+        // Sample rate must be taken before the sample, as exhausted source reports some default
+        // one
+        let current_sample_rate = source.sample_rate();
+        let Some(sample) = source.next() else {
+            break;
+        };
+        if current_sample_rate != sample_rate {
+            return Err(tr_args(
+                Key::SampleRateChanges,
+                &[&sample_rate, &current_sample_rate],
+            ));
+        }
+        samples_count += 1;
+
+        if samples_count > CACHED_SAMPLES_LIMIT as u64 {
+            samples = None;
+        } else if let Some(samples) = &mut samples {
+            samples.push(sample);
+        }
+    }
+    Ok((samples_count, samples))
+}
+
+/// Returns average bitrate of an audio file in kilobits per second.
+///
+/// Compressed formats, like MP3 with a variable bitrate, have no single bitrate, so the one is
+/// computed from the file size, including headers and tags, and the audio track duration.
+///
+/// # Parameters
+///
+/// * `file_size` - size of the audio file in bytes.
+/// * `duration` - duration of the audio track.
+fn average_bitrate(file_size: u64, duration: Duration) -> Option<u32> {
+    if duration.is_zero() {
+        return None;
+    }
+    Some((file_size as f64 * 8.0 / 1000.0 / duration.as_secs_f64()).round() as u32)
+}
+
+/// Returns index of the frame, that is played at the given position.
+///
+/// Frame is a set of samples of all channels, played at the same moment.
+///
+/// # Parameters
+///
+/// * `position` - position on the audio track.
+/// * `sample_rate` - number of frames per second of the audio track.
+pub fn frame_index(position: Duration, sample_rate: u32) -> u64 {
+    // Use integer math, as floating point seconds lose precision on long tracks
+    (position.as_nanos() * u128::from(sample_rate) / 1_000_000_000) as u64
+}
+
+/// Returns the position on the audio track, at which the frame with the given index is played.
+///
+/// It is the inverse of [frame_index].
+///
+/// # Parameters
+///
+/// * `frame` - index of the frame.
+/// * `sample_rate` - number of frames per second of the audio track.
+pub fn frame_position(frame: u64, sample_rate: u32) -> Duration {
+    Duration::from_nanos(
+        (u128::from(frame) * 1_000_000_000 / u128::from(sample_rate.max(1))) as u64,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mono audio source, that plays the given spans of samples one after another, each at its
+    /// own sample rate, like a decoder of a file with the sample rate, changing mid-stream.
+    struct SpansSource {
+        /// Sample rates and numbers of samples of the spans.
+        spans: Vec<(u32, usize)>,
+        /// Index of the playing span.
+        span: usize,
+        /// Number of samples, played from the playing span.
+        played: usize,
+    }
+
+    impl SpansSource {
+        /// Creates an audio source, that plays the given spans of silence.
+        ///
+        /// # Parameters
+        ///
+        /// * `spans` - sample rates and numbers of samples of the spans.
+        fn new(spans: &[(u32, usize)]) -> Self {
+            Self {
+                spans: spans.to_vec(),
+                span: 0,
+                played: 0,
+            }
+        }
+    }
+
+    impl Iterator for SpansSource {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            while self.played == self.spans.get(self.span)?.1 {
+                self.span += 1;
+                self.played = 0;
+            }
+            self.played += 1;
+            Some(0.0)
+        }
+    }
+
+    impl Source for SpansSource {
+        fn current_span_len(&self) -> Option<usize> {
+            self.spans
+                .get(self.span)
+                .map(|&(_, samples)| samples - self.played)
+        }
+
+        fn channels(&self) -> rodio::ChannelCount {
+            1
+        }
+
+        fn sample_rate(&self) -> rodio::SampleRate {
+            // Exhausted source reports some default sample rate, like rodio sources do
+            self.spans
+                .get(self.span)
+                .map_or(44100, |&(sample_rate, _)| sample_rate)
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    /// Returns the test audio track, loaded into a pipeline.
+    fn test_pipeline() -> AudioPipeline {
+        AudioPipeline::load(Path::new(crate::TEST_TRACK)).unwrap()
+    }
+
+    /// Returns options, that write the region as is to a WAV file.
+    fn wav_options() -> ExportOptions {
+        ExportOptions {
+            fades: Fades::default(),
+            normalize: false,
+            gain_db: 0.0,
+            limit: false,
+            channel_mode: ChannelMode::Keep,
+            format: ExportFormat::Wav,
+        }
+    }
+
+    /// Returns path to a file in the temporary directory, unique for this test run.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - name of the file, unique among the tests.
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("audio_cutter_{}_{}", std::process::id(), name))
+    }
+
+    /// Asserts that the durations are equal within a few milliseconds, e.g. a frame or two.
+    ///
+    /// # Parameters
+    ///
+    /// * `actual` - the duration to check.
+    /// * `expected` - the expected duration.
+    fn assert_near(actual: Duration, expected: Duration) {
+        assert!(
+            actual.abs_diff(expected) < Duration::from_millis(5),
+            "{actual:?} is not {expected:?}"
+        );
+    }
+
+    #[test]
+    fn load_reads_track_properties() {
+        let pipeline = test_pipeline();
+
+        assert_eq!(pipeline.channels(), 2);
+        assert_eq!(pipeline.sample_rate(), 8000);
+        assert_eq!(pipeline.duration(), Duration::from_millis(1500));
+        assert!(!pipeline.streamed());
+    }
+
+    #[test]
+    fn streamed_load_keeps_no_samples() {
+        let pipeline =
+            AudioPipeline::load_with_mode(Path::new(crate::TEST_TRACK), LoadMode::Streamed)
+                .unwrap();
+
+        assert!(pipeline.streamed());
+        assert!(pipeline.samples.is_none());
+        assert_eq!(pipeline.duration(), Duration::from_millis(1500));
+        assert_eq!(
+            pipeline
+                .find_sound_segments(0.1, Duration::from_millis(300))
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn load_fails_on_missing_file() {
+        assert!(AudioPipeline::load(&temp_file("missing.wav")).is_err());
+    }
+
+    #[test]
+    fn load_fails_on_text_file_named_as_audio_file() {
+        let error = AudioPipeline::load(Path::new(crate::NOT_AUDIO_FILE))
+            .err()
+            .unwrap();
+
+        // It is not an OGG file, so there is no point in telling about its codec
+        assert!(
+            error.starts_with("File format is not recognized"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn export_region_writes_region() {
+        let file = temp_file("region.wav");
+
+        test_pipeline()
+            .export_region(
+                Duration::from_millis(250),
+                Duration::from_millis(1000),
+                wav_options(),
+                &file,
+                &ExportProgress::default(),
+            )
+            .unwrap();
+
+        let region = AudioPipeline::load(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(region.channels(), 2);
+        assert_eq!(region.sample_rate(), 8000);
+        assert_eq!(region.duration(), Duration::from_millis(750));
+    }
+
+    #[test]
+    fn export_cut_out_writes_rest_of_track() {
+        let file = temp_file("cut_out.wav");
+
+        test_pipeline()
+            .export_cut_out(
+                Duration::from_millis(500),
+                Duration::from_millis(1000),
+                Duration::ZERO,
+                wav_options(),
+                &file,
+                &ExportProgress::default(),
+            )
+            .unwrap();
+
+        let rest = AudioPipeline::load(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(rest.channels(), 2);
+        assert_eq!(rest.sample_rate(), 8000);
+        assert_eq!(rest.duration(), Duration::from_millis(1000));
+        // Only the tones are left, so there is no silence to split at
+        assert_eq!(
+            rest.find_sound_segments(0.1, Duration::from_millis(100))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn export_cut_out_streams_with_crossfade() {
+        let file = temp_file("cut_out_streamed.wav");
+        let pipeline =
+            AudioPipeline::load_with_mode(Path::new(crate::TEST_TRACK), LoadMode::Streamed)
+                .unwrap();
+
+        pipeline
+            .export_cut_out(
+                Duration::from_millis(500),
+                Duration::from_millis(1000),
+                Duration::from_millis(100),
+                wav_options(),
+                &file,
+                &ExportProgress::default(),
+            )
+            .unwrap();
+
+        let rest = AudioPipeline::load(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert!(pipeline.samples.is_none());
+        // The crossfade overlaps the parts before and after the region
+        assert_eq!(rest.duration(), Duration::from_millis(900));
+        assert_eq!(
+            rest.find_sound_segments(0.1, Duration::from_millis(100))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn compute_waveform_follows_track() {
+        let waveform = test_pipeline().compute_waveform();
+
+        let peaks = waveform.peaks();
+        assert!(!peaks.is_empty());
+        assert!((waveform.max_amplitude() - 0.5).abs() < 0.01);
+        // The middle third of the audio track is silent
+        assert_eq!(peaks[peaks.len() / 2], (0.0, 0.0));
+        assert!(peaks[peaks.len() / 6].1 > 0.4);
+    }
+
+    #[test]
+    fn find_sound_segments_splits_at_silence() {
+        let segments = test_pipeline().find_sound_segments(0.1, Duration::from_millis(300));
+
+        assert_eq!(segments.len(), 2);
+        assert_near(segments[0].0, Duration::ZERO);
+        assert_near(segments[0].1, Duration::from_millis(500));
+        assert_near(segments[1].0, Duration::from_millis(1000));
+        assert_near(segments[1].1, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn decode_samples_rejects_changing_sample_rate() {
+        let source = SpansSource::new(&[(48000, 100), (44100, 100)]);
+
+        assert!(decode_samples(source, 48000, true).is_err());
+    }
+
+    #[test]
+    fn decode_samples_accepts_constant_sample_rate() {
+        let source = SpansSource::new(&[(48000, 100), (48000, 50)]);
+
+        let (samples_count, samples) = decode_samples(source, 48000, true).unwrap();
+
+        assert_eq!(samples_count, 150);
+        assert_eq!(samples.unwrap().len(), 150);
+    }
+}
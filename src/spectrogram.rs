@@ -0,0 +1,230 @@
+use eframe::egui;
+use rustfft::num_complex::Complex;
+
+/// Number of frames, transformed at once into frequencies.
+///
+/// At 48 kHz it is about 21 ms of an audio track, which separates frequencies about 47 Hz apart.
+pub const FFT_SIZE: usize = 1024;
+
+/// Number of frequency bins of each column, from zero up to half of the sample rate.
+pub const SPECTROGRAM_BINS: usize = FFT_SIZE / 2;
+
+/// Minimal number of columns, the spectrogram of an audio track is computed for.
+pub const SPECTROGRAM_COLUMNS: usize = 1000;
+
+/// Number of columns per second of an audio track, so the zoomed in spectrogram stays detailed.
+pub const SPECTROGRAM_COLUMNS_PER_SECOND: u64 = 100;
+
+/// Maximal number of columns, the spectrogram of an audio track is computed for.
+///
+/// It keeps the spectrogram within 32 MiB of memory, that is about 11 minutes of an audio track
+/// with [SPECTROGRAM_COLUMNS_PER_SECOND]. Longer audio tracks get longer columns.
+pub const MAX_SPECTROGRAM_COLUMNS: usize = 64 * 1024;
+
+/// Level in decibels relative to the full scale, that is drawn as silence. Quieter frequencies are
+/// drawn the same way.
+const MIN_LEVEL_DB: f32 = -90.0;
+
+/// Colors, between which the intensities are drawn, from silence up to the full scale.
+const PALETTE: [(f32, f32, f32); 5] = [
+    (0.0, 0.0, 0.0),
+    (40.0, 0.0, 90.0),
+    (180.0, 20.0, 80.0),
+    (250.0, 140.0, 0.0),
+    (255.0, 255.0, 180.0),
+];
+
+/// Spectrogram of an audio track, i.e. levels of its frequencies over time, for drawing.
+///
+/// The audio track is split into equal time columns, like the sound wave, and the frequencies of
+/// the last [FFT_SIZE] frames before the end of each column are kept. All channels are mixed down
+/// to mono, so the spectrogram is drawn as a single lane.
+#[derive(Default)]
+pub struct Spectrogram {
+    /// Intensities of [SPECTROGRAM_BINS] frequency bins of each column, from the lowest frequency
+    /// up, where `0` is [MIN_LEVEL_DB] or quieter and `255` is the full scale.
+    intensities: Vec<u8>,
+}
+
+impl Spectrogram {
+    /// Computes the spectrogram of an audio track from its samples.
+    ///
+    /// It takes a while on long audio tracks, so it is better done in the background.
+    ///
+    /// # Parameters
+    ///
+    /// * `samples` - interleaved samples of all channels of the audio track.
+    /// * `channels` - number of channels of the audio track.
+    /// * `sample_rate` - number of frames per second of the audio track.
+    /// * `frames_count` - number of frames in the audio track.
+    pub fn compute(
+        mut samples: impl Iterator<Item = f32>,
+        channels: u16,
+        sample_rate: u32,
+        frames_count: u64,
+    ) -> Self {
+        let columns = (frames_count * SPECTROGRAM_COLUMNS_PER_SECOND
+            / u64::from(sample_rate.max(1)))
+        .clamp(SPECTROGRAM_COLUMNS as u64, MAX_SPECTROGRAM_COLUMNS as u64)
+        .min(frames_count) as usize;
+        if columns == 0 || channels == 0 {
+            return Self::default();
+        }
+
+        // Hann window smooths the edges of the transformed frames, so they don't leak into all
+        // frequencies
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        // Full scale sine wave gets the level of 0 dB
+        let full_scale = window.iter().sum::<f32>() / 2.0;
+        let fft = rustfft::FftPlanner::new().plan_fft_forward(FFT_SIZE);
+        let mut buffer = vec![Complex::default(); FFT_SIZE];
+        let mut scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+
+        // The last frames, mixed down to mono, in a ring. The beginning of the audio track is
+        // preceded with silence
+        let mut history = vec![0.0_f32; FFT_SIZE];
+        let mut intensities = Vec::with_capacity(columns * SPECTROGRAM_BINS);
+        let mut column = 0;
+        for frame in 0..frames_count {
+            let frame_samples = samples.by_ref().take(usize::from(channels));
+            history[frame as usize % FFT_SIZE] = frame_samples.sum::<f32>() / f32::from(channels);
+
+            // Use integer math, so frames are split between columns evenly on long tracks
+            let column_end =
+                (u128::from(frame + 1) * columns as u128 / u128::from(frames_count)) as usize;
+            if column_end == column {
+                continue;
+            }
+            column = column_end;
+
+            // The oldest frame in the ring is the next one to overwrite
+            let oldest = (frame as usize + 1) % FFT_SIZE;
+            for (i, value) in buffer.iter_mut().enumerate() {
+                *value = Complex::new(history[(oldest + i) % FFT_SIZE] * window[i], 0.0);
+            }
+            fft.process_with_scratch(&mut buffer, &mut scratch);
+
+            intensities.extend(buffer[..SPECTROGRAM_BINS].iter().map(|value| {
+                let level_db = 20.0 * (value.norm() / full_scale).max(f32::MIN_POSITIVE).log10();
+                ((1.0 - level_db / MIN_LEVEL_DB).clamp(0.0, 1.0) * 255.0).round() as u8
+            }));
+        }
+
+        Self { intensities }
+    }
+
+    /// Returns number of columns of the spectrogram.
+    pub fn columns(&self) -> usize {
+        self.intensities.len() / SPECTROGRAM_BINS
+    }
+
+    /// Returns an image of the given columns, merged into at most the given number of columns,
+    /// for drawing.
+    ///
+    /// Each pixel column of the image is a merged column, and each pixel row is a frequency bin,
+    /// starting from the highest frequency at the top. Merged columns keep the highest intensity
+    /// of each frequency bin, so short sounds don't vanish, when the spectrogram is zoomed out.
+    ///
+    /// # Parameters
+    ///
+    /// * `columns` - range of the columns to merge. It is cut to the existing columns.
+    /// * `max_columns` - maximal number of merged columns, e.g. width of the drawing in pixels.
+    pub fn image(&self, columns: std::ops::Range<usize>, max_columns: usize) -> egui::ColorImage {
+        let end = columns.end.min(self.columns());
+        let columns = columns.start.min(end)..end;
+        let step = columns.len().div_ceil(max_columns.max(1)).max(1);
+        let width = columns.len().div_ceil(step);
+
+        let mut pixels = vec![egui::Color32::BLACK; width * SPECTROGRAM_BINS];
+        for (x, first) in columns.step_by(step).enumerate() {
+            for bin in 0..SPECTROGRAM_BINS {
+                let intensity = (first..(first + step).min(end))
+                    .map(|column| self.intensities[column * SPECTROGRAM_BINS + bin])
+                    .max()
+                    .unwrap_or_default();
+                pixels[(SPECTROGRAM_BINS - 1 - bin) * width + x] = color(intensity);
+            }
+        }
+
+        egui::ColorImage::new([width, SPECTROGRAM_BINS], pixels)
+    }
+}
+
+/// Returns the color, an intensity of a frequency bin is drawn with.
+///
+/// # Parameters
+///
+/// * `intensity` - intensity of a frequency bin, where `0` is silence and `255` is the full scale.
+fn color(intensity: u8) -> egui::Color32 {
+    let position = f32::from(intensity) / 255.0 * (PALETTE.len() - 1) as f32;
+    let lower = (position.floor() as usize).min(PALETTE.len() - 2);
+    let fraction = position - lower as f32;
+    let (from, to) = (PALETTE[lower], PALETTE[lower + 1]);
+    let mix = |from: f32, to: f32| (from + (to - from) * fraction).round() as u8;
+    egui::Color32::from_rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the spectrogram of a second of 8 kHz stereo full scale sine wave at the frequency of
+    /// the test tone, see [crate::TEST_TRACK].
+    fn tone_spectrogram() -> Spectrogram {
+        let sample_rate = 8000;
+        let samples = (0..sample_rate).flat_map(|frame| {
+            let sample = (std::f32::consts::TAU * 440.0 * frame as f32 / sample_rate as f32).sin();
+            [sample, sample]
+        });
+        Spectrogram::compute(samples, 2, sample_rate, u64::from(sample_rate))
+    }
+
+    #[test]
+    fn compute_peaks_at_tone_frequency() {
+        let spectrogram = tone_spectrogram();
+
+        assert_eq!(spectrogram.columns(), SPECTROGRAM_COLUMNS);
+        let last_column = &spectrogram.intensities[(SPECTROGRAM_COLUMNS - 1) * SPECTROGRAM_BINS..];
+        let (peak_bin, peak) = last_column
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, intensity)| **intensity)
+            .unwrap();
+        // Each bin is 8000 / 1024 Hz wide, so 440 Hz falls into the 56th one
+        assert_eq!(peak_bin, 56);
+        assert!(*peak > 240, "{peak}");
+    }
+
+    #[test]
+    fn image_fits_max_columns() {
+        let spectrogram = tone_spectrogram();
+
+        for max_columns in [1, 7, 300, 999, 1000, 5000] {
+            let image = spectrogram.image(0..SPECTROGRAM_COLUMNS, max_columns);
+
+            assert!(image.width() <= max_columns, "{max_columns}");
+            assert_eq!(image.height(), SPECTROGRAM_BINS);
+        }
+    }
+
+    #[test]
+    fn image_of_no_columns_is_empty() {
+        let spectrogram = tone_spectrogram();
+
+        // The reversed range is written out, as it is not a mistake here
+        let reversed = std::ops::Range {
+            start: 700,
+            end: 300,
+        };
+        for columns in [0..0, 500..500, reversed, 2000..3000] {
+            let image = spectrogram.image(columns.clone(), 100);
+
+            assert_eq!(image.width(), 0, "{columns:?}");
+        }
+        assert_eq!(Spectrogram::default().image(0..10, 0).width(), 0);
+        // Columns past the end are cut off
+        assert_eq!(spectrogram.image(990..2000, 100).width(), 10);
+    }
+}
@@ -0,0 +1,111 @@
+/// Minimal number of columns, the sound wave of an audio track is reduced to.
+///
+/// It is enough to draw one column per pixel on wide screens, when the whole audio track is shown.
+pub const WAVEFORM_COLUMNS: usize = 2000;
+
+/// Number of columns per second of an audio track, so the zoomed in sound wave stays detailed.
+///
+/// Each column is 5 ms long, which is enough to cut between syllables and beats.
+pub const WAVEFORM_COLUMNS_PER_SECOND: u64 = 200;
+
+/// Maximal number of columns, the sound wave of an audio track is reduced to.
+///
+/// It keeps the sound wave within 16 MiB of memory, that is about 3 hours of an audio track with
+/// [WAVEFORM_COLUMNS_PER_SECOND]. Longer audio tracks get longer columns.
+pub const MAX_WAVEFORM_COLUMNS: usize = 2 * 1024 * 1024;
+
+/// Sound wave of an audio track, reduced to peak amplitudes for drawing.
+///
+/// The audio track is split into equal time columns, and the lowest and the highest samples of
+/// each column are kept. There are more columns, than pixels to draw them, so they are merged
+/// back for drawing with [Waveform::reduced_peaks]. All channels are mixed down to mono, so the
+/// sound wave is drawn as a single lane.
+#[derive(Default)]
+pub struct Waveform {
+    /// The lowest and the highest samples of each column, in the order of playback.
+    peaks: Vec<(f32, f32)>,
+    /// The highest absolute sample value over all columns.
+    max_amplitude: f32,
+}
+
+impl Waveform {
+    /// Computes the sound wave of an audio track from its samples.
+    ///
+    /// # Parameters
+    ///
+    /// * `samples` - interleaved samples of all channels of the audio track.
+    /// * `channels` - number of channels of the audio track.
+    /// * `sample_rate` - number of frames per second of the audio track.
+    /// * `frames_count` - number of frames in the audio track.
+    pub fn compute(
+        mut samples: impl Iterator<Item = f32>,
+        channels: u16,
+        sample_rate: u32,
+        frames_count: u64,
+    ) -> Self {
+        let columns = (frames_count * WAVEFORM_COLUMNS_PER_SECOND / u64::from(sample_rate.max(1)))
+            .clamp(WAVEFORM_COLUMNS as u64, MAX_WAVEFORM_COLUMNS as u64)
+            .min(frames_count) as usize;
+        if columns == 0 || channels == 0 {
+            return Self::default();
+        }
+
+        let mut peaks = vec![(0.0_f32, 0.0_f32); columns];
+        for frame in 0..frames_count {
+            let frame_samples = samples.by_ref().take(usize::from(channels));
+            let mono = frame_samples.sum::<f32>() / f32::from(channels);
+
+            // Use integer math, so frames are split between columns evenly on long tracks
+            let column = (u128::from(frame) * columns as u128 / u128::from(frames_count)) as usize;
+            let (lowest, highest) = &mut peaks[column];
+            *lowest = lowest.min(mono);
+            *highest = highest.max(mono);
+        }
+
+        let max_amplitude = peaks.iter().fold(0.0_f32, |max, &(lowest, highest)| {
+            max.max(-lowest).max(highest)
+        });
+        Self {
+            peaks,
+            max_amplitude,
+        }
+    }
+
+    /// Returns the lowest and the highest samples of each column, in the order of playback.
+    pub fn peaks(&self) -> &[(f32, f32)] {
+        &self.peaks
+    }
+
+    /// Returns the given columns, merged into at most the given number of columns, for drawing.
+    ///
+    /// Each item is the index of the first merged column and the lowest and the highest samples
+    /// of the merged columns.
+    ///
+    /// # Parameters
+    ///
+    /// * `columns` - range of the columns to merge. It is cut to the existing columns.
+    /// * `max_columns` - maximal number of merged columns, e.g. width of the drawing in pixels.
+    pub fn reduced_peaks(
+        &self,
+        columns: std::ops::Range<usize>,
+        max_columns: usize,
+    ) -> impl Iterator<Item = (usize, (f32, f32))> + '_ {
+        let end = columns.end.min(self.peaks.len());
+        let columns = columns.start.min(end)..end;
+        let step = columns.len().div_ceil(max_columns.max(1)).max(1);
+        columns.step_by(step).map(move |first| {
+            let merged = self.peaks[first..(first + step).min(end)].iter().fold(
+                (0.0_f32, 0.0_f32),
+                |(lowest, highest), &(column_lowest, column_highest)| {
+                    (lowest.min(column_lowest), highest.max(column_highest))
+                },
+            );
+            (first, merged)
+        })
+    }
+
+    /// Returns the highest absolute sample value over all columns.
+    pub fn max_amplitude(&self) -> f32 {
+        self.max_amplitude
+    }
+}